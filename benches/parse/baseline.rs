@@ -0,0 +1,151 @@
+// The owned parser parse_tle used before TleRef, kept as the baseline the
+// borrowed parsing is measured against. It builds the old TLE's fields,
+// allocating for the name, designator, epoch and assumed decimal fields as
+// the original did. Errors are reduced to None since the benchmark input
+// is all valid.
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use src::parse::{compute_checksum, decode_alpha5, split_tle};
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[allow(dead_code)]
+pub struct OwnedTle {
+    name: String,
+    satellite_number: u32,
+    classification: char,
+    international_designator: String,
+    epoch: i64,
+    date_time: String,
+    first_derivative_mean_motion: f64,
+    second_derivative_mean_motion: f64,
+    drag_term: f64,
+    ephemeris_type: u8,
+    element_number: u64,
+    inclination: f64,
+    right_ascension: f64,
+    eccentricity: f64,
+    argument_of_perigee: f64,
+    mean_anomaly: f64,
+    mean_motion: f64,
+    revolution_number: u32,
+    checksum_mismatches: Vec<(usize, u8, Option<u8>)>,
+    last_updated_epoch: i64,
+}
+
+pub fn parse_tles_owned(tles: String) -> Vec<Option<OwnedTle>> {
+    split_tle(tles).iter().map(|tle| parse_tle_owned(tle)).collect()
+}
+
+fn parse_tle_owned(tle: &str) -> Option<OwnedTle> {
+    let mut lines = tle.lines().map(str::trim).skip_while(|line| line.is_empty()).peekable();
+
+    let name = match lines.peek() {
+        Some(line) if line.starts_with("1 ") => String::new(),
+        _ => {
+            let name = lines.next()?;
+            name.strip_prefix("0 ").unwrap_or(name).trim().to_string()
+        }
+    };
+    let line1 = lines.next()?;
+    let line2 = lines.next()?;
+
+    let mut checksum_mismatches = Vec::new();
+    for (line_number, line) in [(1, line1), (2, line2)] {
+        let expected = compute_checksum(line);
+        let found = line.get(68..69).and_then(|digit| digit.parse::<u8>().ok());
+        if found != Some(expected) {
+            checksum_mismatches.push((line_number, expected, found));
+        }
+    }
+
+    let epoch = get_epoch_from_tle(field(line1, 18..32)?.to_string())?;
+    Some(OwnedTle {
+        name,
+        satellite_number: decode_alpha5(field(line1, 2..7)?)?,
+        classification: parse_field(line1, 7..8)?,
+        international_designator: field(line1, 9..17)?.to_string(),
+        epoch: epoch.timestamp(),
+        date_time: epoch.to_rfc3339(),
+        first_derivative_mean_motion: parse_field(line1, 33..43)?,
+        second_derivative_mean_motion: parse_decimal_point_assumed(field(line1, 44..52)?.to_string())?,
+        drag_term: parse_decimal_point_assumed(field(line1, 53..61)?.to_string())?,
+        ephemeris_type: parse_field(line1, 62..63)?,
+        element_number: parse_field(line1, 64..68)?,
+        inclination: parse_field(line2, 8..16)?,
+        right_ascension: parse_field(line2, 17..25)?,
+        eccentricity: parse_decimal_point_assumed(field(line2, 26..33)?.to_string())?,
+        argument_of_perigee: parse_field(line2, 34..42)?,
+        mean_anomaly: parse_field(line2, 43..51)?,
+        mean_motion: parse_field(line2, 52..63)?,
+        revolution_number: parse_field(line2, 63..68)?,
+        checksum_mismatches,
+        last_updated_epoch: SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64,
+    })
+}
+
+fn field(line: &str, columns: Range<usize>) -> Option<&str> {
+    line.get(columns).map(str::trim)
+}
+
+fn parse_field<T: FromStr>(line: &str, columns: Range<usize>) -> Option<T> {
+    field(line, columns)?.parse::<T>().ok()
+}
+
+fn get_epoch_from_tle(tle_epoch: String) -> Option<DateTime<Utc>> {
+    let mut epoch_year: i32 = tle_epoch.get(0..2)?.parse::<i32>().ok()?;
+    if epoch_year < 57 {
+        epoch_year += 2000;
+    } else {
+        epoch_year += 1900;
+    }
+    let decimal_day: String = tle_epoch[2..].to_string();
+    let full_day: Vec<&str> = decimal_day.split_terminator('.').collect();
+    let mut day_fraction: f64 = (".".to_owned() + full_day.get(1)?).parse::<f64>().ok()?;
+
+    let hours: f64 = (day_fraction * 24.0).floor();
+    day_fraction -= hours / 24.0;
+    let minutes: f64 = (day_fraction * 24.0 * 60.0).floor();
+    day_fraction -= minutes / (24.0 * 60.0);
+    let seconds: f64 = (day_fraction * 24.0 * 60.0 * 60.0).floor();
+    day_fraction -= seconds / (24.0 * 60.0 * 60.0);
+    let milliseconds: f64 = ((day_fraction * 24.0 * 60.0 * 60.0 * 1000.0) + 0.5).floor();
+
+    let date: NaiveDate = NaiveDate::from_ymd_opt(epoch_year, 1, 1)?;
+    let time: NaiveTime = NaiveTime::from_hms_milli_opt(
+        hours as u32,
+        minutes as u32,
+        seconds as u32,
+        milliseconds as u32,
+    )?;
+    let mut date_time = NaiveDateTime::new(date, time);
+    let days = full_day[0].parse::<i64>().ok()?;
+    date_time += Duration::days(days - 1);
+
+    Some(date_time.and_utc())
+}
+
+fn parse_decimal_point_assumed(input: String) -> Option<f64> {
+    if input.contains('+') || input.contains('-') && !input.starts_with('-') || input.matches('-').count() == 2 {
+        let exp_index: usize = match input.rfind('+') {
+            Some(index) => index,
+            None => input.rfind('-')?,
+        };
+
+        let base = if input.starts_with('-') {
+            format!("-0.{}", input.get(1..exp_index)?).parse::<f64>().ok()?
+        } else {
+            format!("0.{}", &input[0..exp_index]).parse::<f64>().ok()?
+        };
+        let exponent = if input[exp_index..].starts_with('+') {
+            input[(exp_index + 1)..].parse::<f64>().ok()?
+        } else {
+            input[exp_index..].parse::<f64>().ok()?
+        };
+        format!("{:.15}", base * 10f64.powf(exponent)).parse::<f64>().ok()
+    } else if input.contains('-') {
+        format!("-0.{}", input.trim_start_matches('-')).parse::<f64>().ok()
+    } else {
+        format!("0.{}", input).parse::<f64>().ok()
+    }
+}
@@ -0,0 +1,58 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use src::parse::{parse_tles, tle_refs};
+
+mod baseline;
+
+const TLES: &str = "NOAA 15
+1 25338U 98030A   24169.93801846  .00000329  00000+0  15393-3 0  9999
+2 25338  98.5680 197.0492 0009520 328.5342  31.5268 14.26605440357297
+GOES 14
+1 35491U 09033A   24169.87063115 -.00000044  00000+0  00000+0 0  9997
+2 35491   0.3405 104.0766 0003981 351.0753  16.3310  1.00272898 54803
+ARKTIKA-M 1
+1 47719U 21016A   24169.25177733  .00000188  00000+0  00000+0 0  9990
+2 47719  63.1394 151.7679 6941537 268.6991  17.9346  2.00610662 24142
+TIANMU-1 03
+1 55973U 23039A   24169.91489940  .00008689  00000+0  36661-3 0  9997
+2 55973  97.4682 320.5271 0009192 154.7813 205.3875 15.23494043 68961
+";
+
+// Roughly the size of the GROUP=active catalog.
+fn catalog() -> String {
+    TLES.repeat(2500)
+}
+
+fn bulk_parse(c: &mut Criterion) {
+    let catalog = catalog();
+    let mut group = c.benchmark_group("bulk_parse");
+    group.throughput(Throughput::Bytes(catalog.len() as u64));
+
+    group.bench_function("owned_baseline", |b| {
+        b.iter(|| baseline::parse_tles_owned(black_box(catalog.clone())))
+    });
+    group.bench_function("parse_tles", |b| {
+        b.iter(|| parse_tles(black_box(catalog.clone())))
+    });
+    group.bench_function("tle_refs_to_tle", |b| {
+        b.iter(|| {
+            tle_refs(black_box(&catalog))
+                .filter_map(|tle| tle.ok()?.to_tle().ok())
+                .count()
+        })
+    });
+    group.bench_function("tle_refs_mean_motion", |b| {
+        b.iter(|| {
+            tle_refs(black_box(&catalog))
+                .filter_map(|tle| tle.ok()?.mean_motion().ok())
+                .sum::<f64>()
+        })
+    });
+    #[cfg(feature = "parallel")]
+    group.bench_function("parse_tles_parallel", |b| {
+        b.iter(|| src::parse::parse_tles_parallel(black_box(&catalog)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bulk_parse);
+criterion_main!(benches);
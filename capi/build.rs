@@ -0,0 +1,19 @@
+// Generates tle_parser.h from the exported functions and types into
+// OUT_DIR. Set TLE_PARSER_UPDATE_HEADER=1 to also refresh the checked in
+// copy in include/, which the build otherwise leaves alone.
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=TLE_PARSER_UPDATE_HEADER");
+
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("Could not read cbindgen.toml");
+    let bindings = cbindgen::generate_with_config(&crate_dir, config)
+        .expect("Could not generate the C header");
+    bindings.write_to_file(format!("{}/tle_parser.h", out_dir));
+    if std::env::var_os("TLE_PARSER_UPDATE_HEADER").is_some_and(|update| update == "1") {
+        bindings.write_to_file(format!("{}/include/tle_parser.h", crate_dir));
+    }
+}
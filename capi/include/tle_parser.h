@@ -0,0 +1,179 @@
+/*
+ * C API for the TLE parser.
+ *
+ * Functions that can fail return a TleStatus and write their result through
+ * the last argument; tle_last_error_message describes the most recent
+ * failure on the calling thread. Handles and strings returned through out
+ * arguments are owned by the caller and released with tle_free,
+ * tle_cache_free and tle_string_free. Cache functions that query Celestrak
+ * block until the request completes.
+ */
+
+#ifndef TLE_PARSER_H
+#define TLE_PARSER_H
+
+/* Generated by cbindgen from capi/src/lib.rs, do not edit. */
+
+#include <stdint.h>
+
+typedef enum TleStatus {
+  TLE_STATUS_OK = 0,
+  /**
+   * A required pointer argument was null.
+   */
+  TLE_STATUS_NULL_POINTER = 1,
+  /**
+   * A string argument was not valid UTF-8.
+   */
+  TLE_STATUS_INVALID_UTF8 = 2,
+  /**
+   * The record could not be parsed.
+   */
+  TLE_STATUS_PARSE_ERROR = 3,
+  /**
+   * A checksum didn't match with TLE_CHECKSUM_MODE_STRICT.
+   */
+  TLE_STATUS_CHECKSUM_MISMATCH = 4,
+  /**
+   * The TLE has a value that can't be written in its columns.
+   */
+  TLE_STATUS_FORMAT_ERROR = 5,
+  /**
+   * No TLE exists for the catalog number.
+   */
+  TLE_STATUS_NOT_FOUND = 6,
+  /**
+   * Celestrak couldn't be queried or the cache file couldn't be used.
+   */
+  TLE_STATUS_FETCH_ERROR = 7,
+  /**
+   * An argument was out of range, such as an unknown checksum mode.
+   */
+  TLE_STATUS_INVALID_ARGUMENT = 8,
+} TleStatus;
+
+/**
+ * How tle_parse treats checksums, passed to it as an integer.
+ */
+typedef enum TleChecksumMode {
+  TLE_CHECKSUM_MODE_STRICT = 0,
+  TLE_CHECKSUM_MODE_WARN = 1,
+  TLE_CHECKSUM_MODE_OFF = 2,
+} TleChecksumMode;
+
+/**
+ * A TLE cache, created by tle_cache_new or tle_cache_load and released
+ * with tle_cache_free.
+ */
+typedef struct TleCache TleCache;
+
+/**
+ * A parsed TLE, created by tle_parse or tle_cache_get and released with tle_free.
+ */
+typedef struct TleHandle TleHandle;
+
+/**
+ * The numeric fields of a TLE. The designator is in COSPAR form
+ * (1998-067A) and empty when the TLE has none. The epoch is in
+ * microseconds since 1970-01-01T00:00:00Z, which holds it exactly.
+ */
+typedef struct TleElements {
+  uint32_t satellite_number;
+  char classification;
+  char international_designator[12];
+  int64_t epoch_unix_microseconds;
+  double first_derivative_mean_motion;
+  double second_derivative_mean_motion;
+  double drag_term;
+  uint32_t ephemeris_type;
+  uint32_t element_number;
+  double inclination;
+  double right_ascension;
+  double eccentricity;
+  double argument_of_perigee;
+  double mean_anomaly;
+  double mean_motion;
+  uint32_t revolution_number;
+  /**
+   * Lines whose checksum didn't match, with TLE_CHECKSUM_MODE_WARN.
+   */
+  uint32_t checksum_mismatches;
+} TleElements;
+
+/**
+ * The message for the last failed call on this thread, or an empty string.
+ * Valid until the next failing call on the same thread.
+ */
+const char *tle_last_error_message(void);
+
+/**
+ * Parses a 3 line (or 2 line, without a name) element. On success *out
+ * holds a new handle to release with tle_free. `mode` is one of the
+ * TleChecksumMode values; anything else is TLE_STATUS_INVALID_ARGUMENT.
+ */
+enum TleStatus tle_parse(const char *text, uint32_t mode, struct TleHandle **out);
+
+void tle_free(struct TleHandle *tle);
+
+/**
+ * The satellite name, empty for 2 line elements. Owned by the handle.
+ */
+const char *tle_name(const struct TleHandle *tle);
+
+enum TleStatus tle_elements(const struct TleHandle *tle, struct TleElements *out);
+
+/**
+ * Writes the TLE back out as a 3 line element (2 lines when it has no
+ * name) with recomputed checksums. On success *out holds a new string to
+ * release with tle_string_free.
+ */
+enum TleStatus tle_format(const struct TleHandle *tle, char **out);
+
+void tle_string_free(char *text);
+
+/**
+ * Computes the checksum of a TLE line (the sum of its digits, plus 1 for
+ * each minus sign, over columns 1-68, modulo 10) into *out.
+ */
+enum TleStatus tle_compute_checksum(const char *line, uint8_t *out);
+
+/**
+ * Returns TLE_STATUS_OK when column 69 of the line holds its checksum and
+ * TLE_STATUS_CHECKSUM_MISMATCH when it doesn't or is missing.
+ */
+enum TleStatus tle_verify_checksum(const char *line);
+
+/**
+ * Creates an empty cache.
+ */
+struct TleCache *tle_cache_new(void);
+
+/**
+ * Reads a cache written by tle_cache_save, or, when path is null or the
+ * file doesn't exist, fills a new cache from Celestrak. Blocks until done.
+ * Records in the response that can't be decoded are left out.
+ */
+enum TleStatus tle_cache_load(const char *path, struct TleCache **out);
+
+/**
+ * Looks up a TLE by catalog number, querying Celestrak when it isn't
+ * cached. On success *out holds a new handle to release with tle_free.
+ */
+enum TleStatus tle_cache_get(struct TleCache *cache,
+                             uint32_t satellite_number,
+                             struct TleHandle **out);
+
+/**
+ * Adds a copy of a parsed TLE to the cache, replacing any with the same
+ * catalog number.
+ */
+enum TleStatus tle_cache_insert(struct TleCache *cache, const struct TleHandle *tle);
+
+/**
+ * Writes the cache as json.
+ */
+enum TleStatus tle_cache_save(const struct TleCache *cache, const char *path);
+
+void tle_cache_free(struct TleCache *cache);
+
+#endif  /* TLE_PARSER_H */
@@ -0,0 +1,448 @@
+// A C API over the parser, serializer, checksums and cache. Records and
+// caches are opaque handles created by this library and released with
+// their free functions. Functions that can fail return a TleStatus; the
+// message for the most recent failure on the calling thread is available
+// from tle_last_error_message.
+#![allow(clippy::missing_safety_doc)]
+
+use src::fetch::{self, Cache, ErrorKind};
+use src::format::format_tle;
+use src::parse::{compute_checksum, parse_tle_with_checksum, ChecksumMode, ParseErrorKind, TLE};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum TleStatus {
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// A string argument was not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// The record could not be parsed.
+    ParseError = 3,
+    /// A checksum didn't match with TLE_CHECKSUM_MODE_STRICT.
+    ChecksumMismatch = 4,
+    /// The TLE has a value that can't be written in its columns.
+    FormatError = 5,
+    /// No TLE exists for the catalog number.
+    NotFound = 6,
+    /// Celestrak couldn't be queried or the cache file couldn't be used.
+    FetchError = 7,
+    /// An argument was out of range, such as an unknown checksum mode.
+    InvalidArgument = 8,
+}
+
+/// How tle_parse treats checksums, passed to it as an integer.
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum TleChecksumMode {
+    Strict = 0,
+    Warn = 1,
+    Off = 2,
+}
+
+/// Reads a TleChecksumMode passed as an integer, which C doesn't
+/// restrict to the enum's values.
+fn checksum_mode(mode: u32) -> Option<ChecksumMode> {
+    match mode {
+        mode if mode == TleChecksumMode::Strict as u32 => Some(ChecksumMode::Strict),
+        mode if mode == TleChecksumMode::Warn as u32 => Some(ChecksumMode::Warn),
+        mode if mode == TleChecksumMode::Off as u32 => Some(ChecksumMode::Off),
+        _ => None,
+    }
+}
+
+/// The numeric fields of a TLE. The designator is in COSPAR form
+/// (1998-067A) and empty when the TLE has none. The epoch is in
+/// microseconds since 1970-01-01T00:00:00Z, which holds it exactly.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TleElements {
+    pub satellite_number: u32,
+    pub classification: c_char,
+    pub international_designator: [c_char; 12],
+    pub epoch_unix_microseconds: i64,
+    pub first_derivative_mean_motion: f64,
+    pub second_derivative_mean_motion: f64,
+    pub drag_term: f64,
+    pub ephemeris_type: u32,
+    pub element_number: u32,
+    pub inclination: f64,
+    pub right_ascension: f64,
+    pub eccentricity: f64,
+    pub argument_of_perigee: f64,
+    pub mean_anomaly: f64,
+    pub mean_motion: f64,
+    pub revolution_number: u32,
+    /// Lines whose checksum didn't match, with TLE_CHECKSUM_MODE_WARN.
+    pub checksum_mismatches: u32,
+}
+
+/// A parsed TLE, created by tle_parse or tle_cache_get and released with tle_free.
+pub struct TleHandle {
+    tle: TLE,
+    name: CString,
+}
+
+/// A TLE cache, created by tle_cache_new or tle_cache_load and released
+/// with tle_cache_free.
+pub struct TleCache {
+    cache: Cache,
+}
+
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn fail(status: TleStatus, message: impl ToString) -> TleStatus {
+    let message = CString::new(message.to_string().replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = message);
+    status
+}
+
+fn fetch_status(e: fetch::Error) -> TleStatus {
+    match e.kind() {
+        ErrorKind::NotFound(_) => fail(TleStatus::NotFound, e),
+        _ => fail(TleStatus::FetchError, e),
+    }
+}
+
+/// The cache's lookups are async; C calls block on a shared runtime.
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Runtime::new().expect("Could not start the tokio runtime"))
+}
+
+unsafe fn read_str<'a>(text: *const c_char) -> Result<&'a str, TleStatus> {
+    if text.is_null() {
+        return Err(fail(TleStatus::NullPointer, "string argument is null"));
+    }
+    CStr::from_ptr(text)
+        .to_str()
+        .map_err(|e| fail(TleStatus::InvalidUtf8, e))
+}
+
+fn new_handle(tle: TLE) -> *mut TleHandle {
+    let name = CString::new(tle.name.replace('\0', "")).unwrap_or_default();
+    Box::into_raw(Box::new(TleHandle { tle, name }))
+}
+
+
+/// The message for the last failed call on this thread, or an empty string.
+/// Valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn tle_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last_error| last_error.borrow().as_ptr())
+}
+
+/// Parses a 3 line (or 2 line, without a name) element. On success *out
+/// holds a new handle to release with tle_free. `mode` is one of the
+/// TleChecksumMode values; anything else is TLE_STATUS_INVALID_ARGUMENT.
+#[no_mangle]
+pub unsafe extern "C" fn tle_parse(text: *const c_char, mode: u32, out: *mut *mut TleHandle) -> TleStatus {
+    if out.is_null() {
+        return fail(TleStatus::NullPointer, "out is null");
+    }
+    let Some(mode) = checksum_mode(mode) else {
+        return fail(TleStatus::InvalidArgument, format!("unknown checksum mode {}", mode));
+    };
+    let text = match read_str(text) {
+        Ok(text) => text,
+        Err(status) => return status,
+    };
+    match parse_tle_with_checksum(text, mode) {
+        Ok(tle) => {
+            *out = new_handle(tle);
+            TleStatus::Ok
+        }
+        Err(e) if e.kind == ParseErrorKind::ChecksumMismatch => fail(TleStatus::ChecksumMismatch, e),
+        Err(e) => fail(TleStatus::ParseError, e),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tle_free(tle: *mut TleHandle) {
+    if !tle.is_null() {
+        drop(Box::from_raw(tle));
+    }
+}
+
+/// The satellite name, empty for 2 line elements. Owned by the handle.
+#[no_mangle]
+pub unsafe extern "C" fn tle_name(tle: *const TleHandle) -> *const c_char {
+    match tle.as_ref() {
+        Some(handle) => handle.name.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tle_elements(tle: *const TleHandle, out: *mut TleElements) -> TleStatus {
+    let (Some(handle), false) = (tle.as_ref(), out.is_null()) else {
+        return fail(TleStatus::NullPointer, "tle or out is null");
+    };
+    let tle = &handle.tle;
+
+    let mut international_designator: [c_char; 12] = [0; 12];
+    if let Some(designator) = tle.international_designator {
+        for (slot, byte) in international_designator.iter_mut().zip(designator.to_string().bytes()) {
+            *slot = byte as c_char;
+        }
+    }
+    *out = TleElements {
+        satellite_number: tle.satellite_number,
+        classification: tle.classification.as_char() as c_char,
+        international_designator,
+        epoch_unix_microseconds: tle.epoch.timestamp_micros(),
+        first_derivative_mean_motion: tle.first_derivative_mean_motion,
+        second_derivative_mean_motion: tle.second_derivative_mean_motion,
+        drag_term: tle.drag_term,
+        ephemeris_type: tle.ephemeris_type,
+        element_number: tle.element_number,
+        inclination: tle.inclination,
+        right_ascension: tle.right_ascension,
+        eccentricity: tle.eccentricity,
+        argument_of_perigee: tle.argument_of_perigee,
+        mean_anomaly: tle.mean_anomaly,
+        mean_motion: tle.mean_motion,
+        revolution_number: tle.revolution_number,
+        checksum_mismatches: tle.checksum_mismatches.len() as u32,
+    };
+    TleStatus::Ok
+}
+
+/// Writes the TLE back out as a 3 line element (2 lines when it has no
+/// name) with recomputed checksums. On success *out holds a new string to
+/// release with tle_string_free.
+#[no_mangle]
+pub unsafe extern "C" fn tle_format(tle: *const TleHandle, out: *mut *mut c_char) -> TleStatus {
+    let (Some(handle), false) = (tle.as_ref(), out.is_null()) else {
+        return fail(TleStatus::NullPointer, "tle or out is null");
+    };
+    match format_tle(&handle.tle) {
+        Ok(text) => {
+            *out = CString::new(text).unwrap_or_default().into_raw();
+            TleStatus::Ok
+        }
+        Err(e) => fail(TleStatus::FormatError, e),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tle_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+/// Computes the checksum of a TLE line (the sum of its digits, plus 1 for
+/// each minus sign, over columns 1-68, modulo 10) into *out.
+#[no_mangle]
+pub unsafe extern "C" fn tle_compute_checksum(line: *const c_char, out: *mut u8) -> TleStatus {
+    if out.is_null() {
+        return fail(TleStatus::NullPointer, "out is null");
+    }
+    match read_str(line) {
+        Ok(line) => {
+            *out = compute_checksum(line);
+            TleStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Returns TLE_STATUS_OK when column 69 of the line holds its checksum and
+/// TLE_STATUS_CHECKSUM_MISMATCH when it doesn't or is missing.
+#[no_mangle]
+pub unsafe extern "C" fn tle_verify_checksum(line: *const c_char) -> TleStatus {
+    match read_str(line) {
+        Ok(line) if src::parse::verify_checksum(line) => TleStatus::Ok,
+        Ok(line) => fail(TleStatus::ChecksumMismatch, format!("checksum does not match line {:?}", line)),
+        Err(status) => status,
+    }
+}
+
+
+/// Creates an empty cache.
+#[no_mangle]
+pub extern "C" fn tle_cache_new() -> *mut TleCache {
+    Box::into_raw(Box::new(TleCache { cache: Cache::new() }))
+}
+
+/// Reads a cache written by tle_cache_save, or, when path is null or the
+/// file doesn't exist, fills a new cache from Celestrak. Blocks until done.
+/// Records in the response that can't be decoded are left out.
+#[no_mangle]
+pub unsafe extern "C" fn tle_cache_load(path: *const c_char, out: *mut *mut TleCache) -> TleStatus {
+    if out.is_null() {
+        return fail(TleStatus::NullPointer, "out is null");
+    }
+    let path = match path.is_null() {
+        true => None,
+        false => match read_str(path) {
+            Ok(path) => Some(path.to_string()),
+            Err(status) => return status,
+        },
+    };
+    match runtime().block_on(fetch::load_tle_cache(path)) {
+        Ok((cache, _skipped)) => {
+            *out = Box::into_raw(Box::new(TleCache { cache }));
+            TleStatus::Ok
+        }
+        Err(e) => fetch_status(e),
+    }
+}
+
+/// Looks up a TLE by catalog number, querying Celestrak when it isn't
+/// cached. On success *out holds a new handle to release with tle_free.
+#[no_mangle]
+pub unsafe extern "C" fn tle_cache_get(cache: *mut TleCache, satellite_number: u32, out: *mut *mut TleHandle) -> TleStatus {
+    let (Some(cache), false) = (cache.as_mut(), out.is_null()) else {
+        return fail(TleStatus::NullPointer, "cache or out is null");
+    };
+    match runtime().block_on(cache.cache.get_tle(satellite_number)) {
+        Ok(tle) => {
+            *out = new_handle(tle.clone());
+            TleStatus::Ok
+        }
+        Err(e) => fetch_status(e),
+    }
+}
+
+/// Adds a copy of a parsed TLE to the cache, replacing any with the same
+/// catalog number.
+#[no_mangle]
+pub unsafe extern "C" fn tle_cache_insert(cache: *mut TleCache, tle: *const TleHandle) -> TleStatus {
+    let (Some(cache), Some(handle)) = (cache.as_mut(), tle.as_ref()) else {
+        return fail(TleStatus::NullPointer, "cache or tle is null");
+    };
+    cache.cache.insert_tles(vec![handle.tle.clone()]);
+    TleStatus::Ok
+}
+
+/// Writes the cache as json.
+#[no_mangle]
+pub unsafe extern "C" fn tle_cache_save(cache: *const TleCache, path: *const c_char) -> TleStatus {
+    let Some(cache) = cache.as_ref() else {
+        return fail(TleStatus::NullPointer, "cache is null");
+    };
+    match read_str(path) {
+        Ok(path) => match cache.cache.to_file(path.to_string()) {
+            Ok(()) => TleStatus::Ok,
+            Err(e) => fetch_status(e),
+        },
+        Err(status) => status,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tle_cache_free(cache: *mut TleCache) {
+    if !cache.is_null() {
+        drop(Box::from_raw(cache));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISS: &CStr = c"ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791";
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(tle_last_error_message()) }.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        unsafe {
+            let mut tle: *mut TleHandle = ptr::null_mut();
+            assert_eq!(tle_parse(ISS.as_ptr(), TleChecksumMode::Strict as u32, &mut tle), TleStatus::Ok);
+            assert_eq!(CStr::from_ptr(tle_name(tle)), c"ISS (ZARYA)");
+
+            let mut elements = std::mem::zeroed::<TleElements>();
+            assert_eq!(tle_elements(tle, &mut elements), TleStatus::Ok);
+            assert_eq!(elements.satellite_number, 25544);
+            assert_eq!(elements.classification, b'U' as c_char);
+            assert_eq!(CStr::from_ptr(elements.international_designator.as_ptr()), c"1998-067A");
+            assert_eq!(elements.epoch_unix_microseconds, 1581654459231072);
+            assert_eq!(elements.mean_motion, 15.49165514);
+
+            let mut text: *mut c_char = ptr::null_mut();
+            assert_eq!(tle_format(tle, &mut text), TleStatus::Ok);
+            assert!(CStr::from_ptr(text).to_str().unwrap().starts_with("ISS (ZARYA)\n1 25544U 98067A   20045.18587073"));
+            tle_string_free(text);
+            tle_free(tle);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        unsafe {
+            let mut tle: *mut TleHandle = ptr::null_mut();
+            assert_eq!(tle_parse(c"ISS (ZARYA)".as_ptr(), TleChecksumMode::Warn as u32, &mut tle), TleStatus::ParseError);
+            assert!(last_error().starts_with("line 1"));
+            assert!(tle.is_null());
+            assert_eq!(tle_parse(ptr::null(), TleChecksumMode::Warn as u32, &mut tle), TleStatus::NullPointer);
+            assert_eq!(tle_parse(ISS.as_ptr(), 3, &mut tle), TleStatus::InvalidArgument);
+            assert_eq!(last_error(), "unknown checksum mode 3");
+            assert!(tle.is_null());
+
+            let bad_checksum = ISS.to_str().unwrap().replace("0  9990", "0  9991");
+            let bad_checksum = CString::new(bad_checksum).unwrap();
+            assert_eq!(tle_parse(bad_checksum.as_ptr(), TleChecksumMode::Strict as u32, &mut tle), TleStatus::ChecksumMismatch);
+            assert_eq!(tle_parse(bad_checksum.as_ptr(), TleChecksumMode::Warn as u32, &mut tle), TleStatus::Ok);
+            let mut elements = std::mem::zeroed::<TleElements>();
+            tle_elements(tle, &mut elements);
+            assert_eq!(elements.checksum_mismatches, 1);
+            tle_free(tle);
+        }
+    }
+
+    #[test]
+    fn test_checksum() {
+        unsafe {
+            let line = c"1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990";
+            let mut checksum = 10;
+            assert_eq!(tle_compute_checksum(line.as_ptr(), &mut checksum), TleStatus::Ok);
+            assert_eq!(checksum, 0);
+            assert_eq!(tle_verify_checksum(line.as_ptr()), TleStatus::Ok);
+            assert_eq!(tle_verify_checksum(c"1 25544U".as_ptr()), TleStatus::ChecksumMismatch);
+        }
+    }
+
+    #[test]
+    fn test_cache() {
+        unsafe {
+            let mut tle: *mut TleHandle = ptr::null_mut();
+            tle_parse(ISS.as_ptr(), TleChecksumMode::Warn as u32, &mut tle);
+            let cache = tle_cache_new();
+            assert_eq!(tle_cache_insert(cache, tle), TleStatus::Ok);
+            tle_free(tle);
+
+            let mut cached: *mut TleHandle = ptr::null_mut();
+            assert_eq!(tle_cache_get(cache, 25544, &mut cached), TleStatus::Ok);
+            assert_eq!(CStr::from_ptr(tle_name(cached)), c"ISS (ZARYA)");
+            tle_free(cached);
+
+            let path = std::env::temp_dir().join(format!("tle_parser_capi_cache_{}.json", std::process::id()));
+            let path = CString::new(path.to_str().unwrap()).unwrap();
+            assert_eq!(tle_cache_save(cache, path.as_ptr()), TleStatus::Ok);
+            tle_cache_free(cache);
+
+            let mut loaded: *mut TleCache = ptr::null_mut();
+            assert_eq!(tle_cache_load(path.as_ptr(), &mut loaded), TleStatus::Ok);
+            assert_eq!(tle_cache_get(loaded, 25544, &mut cached), TleStatus::Ok);
+            tle_free(cached);
+            tle_cache_free(loaded);
+            std::fs::remove_file(path.to_str().unwrap()).unwrap();
+        }
+    }
+}
@@ -0,0 +1,10 @@
+// The unit tests embed Python, so their binary links libpython. Point the
+// loader at the interpreter's library directory so `cargo test` can run it
+// without LD_LIBRARY_PATH. Wheels are built with pyo3's extension-module
+// feature, don't link libpython and so never use the path.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if let Some(lib_dir) = pyo3_build_config::get().lib_dir() {
+        println!("cargo:rustc-link-arg=-Wl,-rpath,{}", lib_dir);
+    }
+}
@@ -0,0 +1,313 @@
+use chrono::{DateTime, Utc};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyConnectionError, PyIOError, PyKeyError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use src::fetch::{self, Cache, ErrorKind};
+use src::parse::{self, TLE};
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+
+
+create_exception!(tle_parser, ParseError, PyValueError, "A TLE record could not be parsed.");
+create_exception!(tle_parser, FetchError, PyConnectionError, "Celestrak could not be queried.");
+
+// The cache's methods are async; Python calls block on a shared runtime
+// with the GIL released.
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Runtime::new().expect("Could not start the tokio runtime"))
+}
+
+// Unknown catalog numbers raise KeyError, failed requests to Celestrak
+// FetchError (a ConnectionError), cache file problems OSError and
+// anything else, such as a response that can't be parsed, RuntimeError.
+fn fetch_error(e: fetch::Error) -> PyErr {
+    match e.kind() {
+        ErrorKind::NotFound(_) => PyKeyError::new_err(e.to_string()),
+        ErrorKind::HttpRequest(_) | ErrorKind::Msg(_) => FetchError::new_err(e.to_string()),
+        ErrorKind::Io(_) => PyIOError::new_err(e.to_string()),
+        _ => PyRuntimeError::new_err(e.to_string()),
+    }
+}
+
+
+// A parsed TLE. Attributes mirror the fields of the Rust struct; the
+// classification is its letter and the designator is in COSPAR form.
+#[pyclass(name = "TLE", module = "tle_parser", frozen)]
+struct PyTle(TLE);
+
+#[pymethods]
+impl PyTle {
+    #[getter]
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    #[getter]
+    fn satellite_number(&self) -> u32 {
+        self.0.satellite_number
+    }
+
+    #[getter]
+    fn classification(&self) -> char {
+        self.0.classification.as_char()
+    }
+
+    #[getter]
+    fn international_designator(&self) -> Option<String> {
+        self.0.international_designator.map(|designator| designator.to_string())
+    }
+
+    #[getter]
+    fn epoch(&self) -> DateTime<Utc> {
+        self.0.epoch
+    }
+
+    #[getter]
+    fn epoch_text(&self) -> Option<String> {
+        self.0.epoch_text()
+    }
+
+    #[getter]
+    fn first_derivative_mean_motion(&self) -> f64 {
+        self.0.first_derivative_mean_motion
+    }
+
+    #[getter]
+    fn second_derivative_mean_motion(&self) -> f64 {
+        self.0.second_derivative_mean_motion
+    }
+
+    #[getter]
+    fn drag_term(&self) -> f64 {
+        self.0.drag_term
+    }
+
+    #[getter]
+    fn ephemeris_type(&self) -> u32 {
+        self.0.ephemeris_type
+    }
+
+    #[getter]
+    fn element_number(&self) -> u32 {
+        self.0.element_number
+    }
+
+    #[getter]
+    fn inclination(&self) -> f64 {
+        self.0.inclination
+    }
+
+    #[getter]
+    fn right_ascension(&self) -> f64 {
+        self.0.right_ascension
+    }
+
+    #[getter]
+    fn eccentricity(&self) -> f64 {
+        self.0.eccentricity
+    }
+
+    #[getter]
+    fn argument_of_perigee(&self) -> f64 {
+        self.0.argument_of_perigee
+    }
+
+    #[getter]
+    fn mean_anomaly(&self) -> f64 {
+        self.0.mean_anomaly
+    }
+
+    #[getter]
+    fn mean_motion(&self) -> f64 {
+        self.0.mean_motion
+    }
+
+    #[getter]
+    fn revolution_number(&self) -> u32 {
+        self.0.revolution_number
+    }
+
+    // (line, expected, found) for each line whose checksum didn't match.
+    #[getter]
+    fn checksum_mismatches(&self) -> Vec<(usize, u8, Option<u8>)> {
+        self.0
+            .checksum_mismatches
+            .iter()
+            .map(|mismatch| (mismatch.line, mismatch.expected, mismatch.found))
+            .collect()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TLE(name={:?}, satellite_number={}, epoch={:?})",
+            self.0.name,
+            self.0.satellite_number,
+            self.0.epoch_text().unwrap_or_default(),
+        )
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+
+// Parses a 3 line (or 2 line, without a name) element.
+#[pyfunction]
+fn parse_tle(tle: &str) -> PyResult<PyTle> {
+    parse::parse_tle(tle)
+        .map(PyTle)
+        .map_err(|e| ParseError::new_err(e.to_string()))
+}
+
+// Splits a catalog into the text of each record.
+#[pyfunction]
+fn split_tle(tles: String) -> Vec<String> {
+    parse::split_tle(tles)
+}
+
+
+// The TLE cache, backed by Celestrak. Methods that may query Celestrak
+// block until the request completes.
+#[pyclass(name = "Cache", module = "tle_parser")]
+struct PyCache(Cache);
+
+#[pymethods]
+impl PyCache {
+    #[new]
+    fn new() -> Self {
+        PyCache(Cache::new())
+    }
+
+    // Reads the cache from a json file, or fills it with a bulk query when
+    // no path is given or the file doesn't exist. Records in the response
+    // that can't be decoded are left out; use update to see them.
+    #[staticmethod]
+    #[pyo3(signature = (path=None))]
+    fn load(py: Python<'_>, path: Option<String>) -> PyResult<Self> {
+        py.detach(|| runtime().block_on(fetch::load_tle_cache(path)))
+            .map(|(cache, _skipped)| PyCache(cache))
+            .map_err(fetch_error)
+    }
+
+    // Gets a TLE from the cache, querying Celestrak if it isn't cached.
+    // Raises KeyError when no TLE exists for the catalog number.
+    fn get(&mut self, py: Python<'_>, satellite_number: u32) -> PyResult<PyTle> {
+        let cache = &mut self.0;
+        py.detach(|| runtime().block_on(cache.get_tle(satellite_number)).cloned())
+            .map(PyTle)
+            .map_err(fetch_error)
+    }
+
+    // Bulk updates the cache, returning a description of each record in
+    // the response that was skipped.
+    fn update(&mut self, py: Python<'_>) -> PyResult<Vec<String>> {
+        let cache = &mut self.0;
+        py.detach(|| runtime().block_on(cache.update()))
+            .map(|skipped| skipped.iter().map(|record| record.to_string()).collect())
+            .map_err(fetch_error)
+    }
+
+    fn to_file(&self, path: String) -> PyResult<()> {
+        self.0.to_file(path).map_err(fetch_error)
+    }
+}
+
+
+#[pymodule]
+fn tle_parser(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(parse_tle, module)?)?;
+    module.add_function(wrap_pyfunction!(split_tle, module)?)?;
+    module.add_class::<PyTle>()?;
+    module.add_class::<PyCache>()?;
+    module.add("ParseError", module.py().get_type::<ParseError>())?;
+    module.add("FetchError", module.py().get_type::<FetchError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+    use std::ffi::CString;
+
+    const ISS: &str = "ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791";
+
+    // Runs Python code against the module, imported as tle_parser.
+    fn run(code: &str) -> PyResult<()> {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "tle_parser")?;
+            tle_parser(&module)?;
+            let globals = PyDict::new(py);
+            globals.set_item("tle_parser", module)?;
+            globals.set_item("ISS", ISS)?;
+            py.run(&CString::new(code).unwrap(), Some(&globals), None)
+        })
+    }
+
+    #[test]
+    fn test_tle() {
+        run(r#"
+import datetime
+tle = tle_parser.parse_tle(ISS)
+assert tle.name == "ISS (ZARYA)"
+assert tle.satellite_number == 25544
+assert tle.classification == "U"
+assert tle.international_designator == "1998-067A"
+assert tle.epoch == datetime.datetime(2020, 2, 14, 4, 27, 39, 231072, tzinfo=datetime.timezone.utc)
+assert tle.epoch_text == "20045.18587073"
+assert tle.drag_term == 0.25302e-4
+assert tle.mean_motion == 15.49165514
+assert tle.revolution_number == 21279
+assert tle.checksum_mismatches == []
+assert tle == tle_parser.parse_tle(ISS)
+assert repr(tle) == 'TLE(name="ISS (ZARYA)", satellite_number=25544, epoch="20045.18587073")'
+assert str(tle).startswith("ISS (ZARYA)\nSatellite #: 25544\n")
+
+bad = tle_parser.parse_tle(ISS.replace("0  9990", "0  9991"))
+assert bad.checksum_mismatches == [(1, 0, 1)]
+assert tle_parser.split_tle(ISS + "\n" + ISS) == [ISS + "\n"] * 2
+"#).unwrap();
+    }
+
+    #[test]
+    fn test_errors() {
+        run(r#"
+try:
+    tle_parser.parse_tle("ISS (ZARYA)")
+    raise AssertionError("parsed a name on its own")
+except tle_parser.ParseError as e:
+    assert isinstance(e, ValueError)
+    assert str(e).startswith("line 1")
+
+cache = tle_parser.Cache()
+try:
+    cache.to_file("/nonexistent/directory/cache.json")
+    raise AssertionError("wrote to a missing directory")
+except OSError as e:
+    assert not isinstance(e, ConnectionError)
+"#).unwrap();
+
+        Python::attach(|py| {
+            let not_found = fetch_error(fetch::ErrorKind::NotFound(99999).into());
+            assert!(not_found.is_instance_of::<PyKeyError>(py));
+            assert_eq!(not_found.value(py).to_string(), "'No TLE found with catalog number 99999.'");
+
+            let query = fetch_error("Query to celestrak failed with code 503".into());
+            assert!(query.is_instance_of::<FetchError>(py));
+            assert!(query.is_instance_of::<PyConnectionError>(py));
+            assert!(!query.is_instance_of::<PyKeyError>(py));
+
+            let parse = parse::parse_tle("ISS (ZARYA)").unwrap_err();
+            assert!(fetch_error(parse.into()).is_instance_of::<PyRuntimeError>(py));
+        });
+    }
+}
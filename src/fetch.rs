@@ -1,25 +1,58 @@
-use crate::parse::{parse_tle, split_tle, TLE};
-use chrono::Utc;
+use crate::parse::{parse_omm_csv, parse_omm_json, parse_omm_xml, parse_tle, split_tle_records, StrayLine, TLE};
+use crate::pass::Pass;
+use crate::station::{GroundStation, LookAngles};
+use crate::stream::{read_tles_async, ReadError};
+use chrono::{DateTime, Utc};
 use error_chain::error_chain;
+use futures::{Stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use tokio_util::io::StreamReader;
 
 error_chain! {
     foreign_links {
         Io(std::io::Error);
+        Json(serde_json::Error);
         HttpRequest(reqwest::Error);
+        Parse(crate::parse::ParseError);
+        Omm(crate::parse::OmmError);
+        Sgp4(crate::sgp4::Sgp4Error);
+    }
+
+    errors {
+        NotFound(sat_num: u32) {
+            description("no TLE found")
+            display("No TLE found with catalog number {}.", sat_num)
+        }
     }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Cache {
     last_bulk_update: i64,
+    #[serde(default)]
+    format: QueryFormat,
     tles: Vec<TLE>,
 }
 
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Cache {
     pub fn new() -> Self {
-        Cache {last_bulk_update: -1, tles: Vec::new()}
+        Cache {last_bulk_update: -1, format: QueryFormat::default(), tles: Vec::new()}
+    }
+
+    /*
+    Sets the format the cache requests from Celestrak when
+    updating or fetching TLE's. The cached TLE's are the same
+    regardless of the format they were decoded from.
+    */
+    pub fn set_format(&mut self, format: QueryFormat) {
+        self.format = format;
     }
 
     /* 
@@ -27,24 +60,33 @@ impl Cache {
     specified location, which should end in `.json`.
     */
     pub fn to_file(&self, path: String) -> Result<()> {
-        fs::write(
-            path,
-            serde_json::to_string_pretty(self).expect("Failed to serialise TLE's"),
-        )?;
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
         Ok(())
     }
 
     /*
     Bulk updates the TLE's in the cache by querying Celestrak
-    with "GROUP=active". Returns a reference to the cache.
+    with "GROUP=active". TLE format responses are parsed as they
+    are received rather than buffered, or in parallel with the
+    `parallel` feature. Returns the records in the response
+    that could not be decoded and were left out of the cache.
+    */
+    pub async fn update(&mut self) -> Result<Vec<SkippedRecord>> {
+        let (updated_tles, skipped) = fetch_bulk("GROUP=active".to_string(), self.format).await?;
+        self.insert_tles(updated_tles);
+
+        // Update the timestamp of the last bulk update
+        self.last_bulk_update = Utc::now().timestamp();
+        Ok(skipped)
+    }
+
+    /*
+    Adds TLE's to the cache, replacing any existing TLE with
+    the same Satellite Number. The TLE's can come from any
+    source, e.g. `parse_tle` or `parse_omm_xml`.
     */
-    pub async fn update(&mut self) -> Result<&Cache> {
-        let updated_tles =
-            split_tle(fetch_tle("GROUP=active".to_string(), QueryType::Standard).await?)
-                .iter()
-                .map(parse_tle)
-                .collect::<Vec<TLE>>();
-        for new_tle in updated_tles {
+    pub fn insert_tles(&mut self, tles: Vec<TLE>) {
+        for new_tle in tles {
             if let Some(existing_tle) = self
                 .tles
                 .iter_mut()
@@ -55,10 +97,6 @@ impl Cache {
                 self.tles.push(new_tle); // Add new TLE
             }
         }
-
-        // Update the timestamp of the last bulk update
-        self.last_bulk_update = Utc::now().timestamp();
-        Ok(self)
     }
 
     /* 
@@ -66,62 +104,210 @@ impl Cache {
     First tries the cache then tries a standard gp query,
     if that fails, tries a supplementary gp query. If all 
     fails returns an error, if any query succeeds returns
-    tle and adds it to the cache.
+    tle and adds it to the cache. Catalog numbers above 99999
+    are queried numerically and matched against the Alpha-5
+    number in the response.
     */
     pub async fn get_tle(&mut self, sat_num: u32) -> std::result::Result<&TLE, Error> {
+        let format = self.format;
         let tles: &mut Vec<TLE> = &mut self.tles;
         if let Some(index) = tles.iter().position(|tle| tle.satellite_number == sat_num) {
             return Ok(&tles[index]);
         }
 
-        let res = split_tle(fetch_tle(format!("CATNR={}", sat_num), QueryType::Standard).await?);
-        if let Some(tle) = res.first() {
-            tles.push(parse_tle(tle));
+        let res = fetch_tle(format!("CATNR={}", sat_num), QueryType::Standard, format).await?;
+        if let Some(tle) = find_tle(&res, format, sat_num) {
+            tles.push(tle);
             return Ok(tles.last().unwrap());
         }
 
         let res =
-            split_tle(fetch_tle(format!("CATNR={}", sat_num), QueryType::Supplementary).await?);
-        if let Some(tle) = res.first() {
-            tles.push(parse_tle(tle));
+            fetch_tle(format!("CATNR={}", sat_num), QueryType::Supplementary, format).await?;
+        if let Some(tle) = find_tle(&res, format, sat_num) {
+            tles.push(tle);
             return Ok(tles.last().unwrap());
         }
 
-        Err(format!("No TLE found with catalog number {}.", sat_num).into())
+        Err(ErrorKind::NotFound(sat_num).into())
+    }
+
+    /*
+    Fetches the TLE for the given Satellite Number as `get_tle`
+    does and returns the look angles to it from the station at
+    each of the given times.
+    */
+    pub async fn look_angles<I>(&mut self, sat_num: u32, station: &GroundStation, times: I) -> Result<Vec<LookAngles>>
+    where
+        I: IntoIterator<Item = DateTime<Utc>>,
+    {
+        let tle = self.get_tle(sat_num).await?;
+        Ok(station.look_angles_series(tle, times)?)
+    }
+
+    /*
+    Predicts the passes over the station of each of the given
+    Satellite Numbers between start and end, fetching TLE's as
+    `get_tle` does. The passes of all the satellites are
+    returned together in order of AOS. A satellite whose TLE
+    can't be fetched or propagated doesn't stop the others,
+    it is returned with its error alongside the passes.
+    */
+    pub async fn passes(
+        &mut self,
+        sat_nums: &[u32],
+        station: &GroundStation,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        minimum_elevation: f64,
+    ) -> (Vec<Pass>, Vec<(u32, Error)>) {
+        let mut passes = Vec::new();
+        let mut failures = Vec::new();
+        for &sat_num in sat_nums {
+            let predicted = match self.get_tle(sat_num).await {
+                Ok(tle) => station.passes(tle, start, end, minimum_elevation).map_err(Error::from),
+                Err(e) => Err(e),
+            };
+            match predicted {
+                Ok(predicted) => passes.extend(predicted),
+                Err(e) => failures.push((sat_num, e)),
+            }
+        }
+        passes.sort_by_key(|pass| (pass.aos, pass.satellite_number));
+        (passes, failures)
     }
 }
 
 /*
 Creates a new in-memory cache either by reading from a 
 json file containing it or initalising it with a 
-`GROUP=active` query to celestrak.
+`GROUP=active` query to celestrak. Also returns the records
+the query skipped, which is always empty for a cache file.
 */
-pub async fn load_tle_cache(path: Option<String>) -> Result<Cache> {
-    if path.is_none() {
-        let cache: Cache = Cache {
-            last_bulk_update: Utc::now().timestamp(),
-            tles: split_tle(fetch_tle("GROUP=active".to_string(), QueryType::Standard).await?)
-                .iter()
-                .map(parse_tle)
-                .collect(),
-        };
-        return Ok(cache);
-    } else {
-        if !fs::metadata(path.as_ref().unwrap()).is_ok() {
-            let cache: Cache = Cache {
-                last_bulk_update: Utc::now().timestamp(),
-                tles: split_tle(fetch_tle("GROUP=active".to_string(), QueryType::Standard).await?)
-                    .iter()
-                    .map(parse_tle)
-                    .collect(),
-            };
-            return Ok(cache);
-        } else {
-            let file_contents =
-                fs::read_to_string(path.as_ref().unwrap()).expect("Could not read file contents.");
-            Ok(serde_json::from_str(&file_contents).expect("Could not deserialise file_contents"))
+pub async fn load_tle_cache(path: Option<String>) -> Result<(Cache, Vec<SkippedRecord>)> {
+    match path {
+        Some(path) if fs::metadata(&path).is_ok() => {
+            let file_contents = fs::read_to_string(&path)?;
+            Ok((serde_json::from_str(&file_contents)?, Vec::new()))
+        }
+        _ => {
+            let mut cache = Cache::new();
+            let skipped = cache.update().await?;
+            Ok((cache, skipped))
+        }
+    }
+}
+
+/*
+A record left out of a bulk update, either because it failed
+to decode or, for TLE format responses, because it was a line
+that didn't belong to any record.
+*/
+#[derive(Debug)]
+pub enum SkippedRecord {
+    Malformed(Error),
+    StrayLine(StrayLine),
+}
+
+impl std::fmt::Display for SkippedRecord {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkippedRecord::Malformed(e) => write!(formatter, "malformed record: {}", e),
+            SkippedRecord::StrayLine(line) => {
+                write!(formatter, "stray line {}: {:?}", line.line_number, line.text)
+            }
+        }
+    }
+}
+
+/*
+Runs a bulk standard query and parses every record in the
+response, skipping any record that fails to parse so one
+malformed TLE does not prevent the rest from being loaded.
+The skipped records are returned alongside the TLE's. Reading
+the response failing part way through is still an error.
+*/
+async fn fetch_bulk(query: String, format: QueryFormat) -> Result<(Vec<TLE>, Vec<SkippedRecord>)> {
+    let mut tles = Vec::new();
+    let mut skipped = Vec::new();
+
+    // With the parallel feature the whole response is fetched and then
+    // decoded below, parsing the records across all cores, rather than
+    // streamed.
+    if format == QueryFormat::Tle && !cfg!(feature = "parallel") {
+        let mut stream = Box::pin(stream_tles(query, QueryType::Standard).await?);
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(tle) => tles.push(tle),
+                Err(ReadError::Io(e)) => return Err(e.into()),
+                Err(ReadError::Parse(e)) => skipped.push(SkippedRecord::Malformed(e.into())),
+                Err(ReadError::StrayLine(line)) => skipped.push(SkippedRecord::StrayLine(line)),
+            }
+        }
+        return Ok((tles, skipped));
+    }
+
+    let body = fetch_tle(query, QueryType::Standard, format).await?;
+    let (results, stray_lines) = decode_tles(&body, format);
+    for result in results {
+        match result {
+            Ok(tle) => tles.push(tle),
+            Err(e) => skipped.push(SkippedRecord::Malformed(e)),
         }
     }
+    skipped.extend(stray_lines.into_iter().map(SkippedRecord::StrayLine));
+    Ok((tles, skipped))
+}
+
+/*
+Decodes a query response in the given format into TLE's,
+keeping the result for every record along with any stray
+lines in a TLE format response. Celestrak answers a query
+with no matches in plain text regardless of format, which
+is decoded as no records.
+*/
+fn decode_tles(body: &str, format: QueryFormat) -> (Vec<std::result::Result<TLE, Error>>, Vec<StrayLine>) {
+    if body.trim_start().starts_with("No GP data found") {
+        return (Vec::new(), Vec::new());
+    }
+    let results = match format {
+        QueryFormat::Tle => {
+            let split = split_tle_records(body);
+            return (parse_records(&split.records), split.stray_lines);
+        }
+        QueryFormat::Json => parse_omm_json(body).into_iter().map(|r| r.map_err(Error::from)).collect(),
+        QueryFormat::Csv => parse_omm_csv(body).into_iter().map(|r| r.map_err(Error::from)).collect(),
+        QueryFormat::Xml => parse_omm_xml(body).into_iter().map(|r| r.map_err(Error::from)).collect(),
+    };
+    (results, Vec::new())
+}
+
+/*
+Parses split TLE records, across all cores with the parallel
+feature.
+*/
+#[cfg(feature = "parallel")]
+fn parse_records(records: &[String]) -> Vec<std::result::Result<TLE, Error>> {
+    use rayon::prelude::*;
+    records.par_iter().map(|record| parse_tle(record).map_err(Error::from)).collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn parse_records(records: &[String]) -> Vec<std::result::Result<TLE, Error>> {
+    records.iter().map(|record| parse_tle(record).map_err(Error::from)).collect()
+}
+
+/*
+Decodes the records returned by a CATNR query and returns the
+one with the requested catalog number, decoding Alpha-5 numbers
+so ids above 99999 are matched. Malformed records are skipped
+so they can't hide the requested one later in the response.
+*/
+fn find_tle(body: &str, format: QueryFormat, sat_num: u32) -> Option<TLE> {
+    decode_tles(body, format)
+        .0
+        .into_iter()
+        .filter_map(std::result::Result::ok)
+        .find(|tle| tle.satellite_number == sat_num)
 }
 
 #[derive(PartialEq)]
@@ -130,7 +316,46 @@ pub enum QueryType {
     Supplementary,
 }
 
-async fn fetch_tle(query: String, request_type: QueryType) -> std::result::Result<String, Error> {
+/*
+The format requested from Celestrak. `Tle` is the classic
+fixed-width format, the others are CCSDS OMM encodings which
+carry full precision epochs and elements.
+*/
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug, Default)]
+pub enum QueryFormat {
+    #[default]
+    Tle,
+    Json,
+    Csv,
+    Xml,
+}
+
+impl QueryFormat {
+    fn as_query(&self) -> &'static str {
+        match self {
+            QueryFormat::Tle => "tle",
+            QueryFormat::Json => "json",
+            QueryFormat::Csv => "csv",
+            QueryFormat::Xml => "xml",
+        }
+    }
+}
+
+/*
+Runs a TLE format query against Celestrak and returns a stream
+of the TLE's in the response, parsed record by record as the
+body arrives so large responses are never held in memory.
+*/
+pub async fn stream_tles(
+    query: String,
+    request_type: QueryType,
+) -> Result<impl Stream<Item = std::result::Result<TLE, ReadError>>> {
+    let res = query_celestrak(query, request_type, QueryFormat::Tle).await?;
+    let body = StreamReader::new(res.bytes_stream().map_err(std::io::Error::other));
+    Ok(read_tles_async(body))
+}
+
+async fn query_celestrak(query: String, request_type: QueryType, format: QueryFormat) -> std::result::Result<reqwest::Response, Error> {
     let mut query_type = "gp";
     if request_type == QueryType::Standard {
         query_type = "gp";
@@ -139,16 +364,19 @@ async fn fetch_tle(query: String, request_type: QueryType) -> std::result::Resul
     }
 
     let res = reqwest::get(&format!(
-        "https://celestrak.org/NORAD/elements/{}.php?{}&FORMAT=tle",
-        query_type, query
+        "https://celestrak.org/NORAD/elements/{}.php?{}&FORMAT={}",
+        query_type, query, format.as_query()
     ))
     .await?;
 
     if res.status() != 200 {
         return Err(format!("Query to celestrak failed with code {}", res.status()).into());
     }
+    Ok(res)
+}
 
-    let body = res.text().await?;
+async fn fetch_tle(query: String, request_type: QueryType, format: QueryFormat) -> std::result::Result<String, Error> {
+    let body = query_celestrak(query, request_type, format).await?.text().await?;
 
         /* let body = "NOAA 15
     1 25338U 98030A   24169.93801846  .00000329  00000+0  15393-3 0  9999
@@ -369,3 +597,69 @@ async fn fetch_tle(query: String, request_type: QueryType) -> std::result::Resul
             .to_string(); */
     Ok(body)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_load_tle_cache_errors() {
+        let path = std::env::temp_dir().join(format!("tle_parser_fetch_cache_{}.json", std::process::id()));
+        fs::write(&path, "{\"last_bulk_update\": -1, \"tles\": [").unwrap();
+        let result = load_tle_cache(Some(path.to_string_lossy().to_string())).await;
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(Error(ErrorKind::Json(_), _))));
+
+        let missing_directory = std::env::temp_dir().join("tle_parser_missing_directory").join("cache.json");
+        let result = Cache::new().to_file(missing_directory.to_string_lossy().to_string());
+        assert!(matches!(result, Err(Error(ErrorKind::Io(_), _))));
+    }
+
+    #[tokio::test]
+    async fn test_passes_failures() {
+        let iss = parse_tle("ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap();
+        let mut stopped = iss.clone();
+        stopped.satellite_number = 99998;
+        stopped.mean_motion = 0.0;
+        let mut cache = Cache::new();
+        cache.insert_tles(vec![stopped, iss.clone()]);
+
+        let station = GroundStation::new(crate::frame::Geodetic {latitude: 51.48, longitude: -0.01, altitude: 0.05});
+        let (start, end) = (iss.epoch, iss.epoch + chrono::Duration::days(1));
+        let (passes, failures) = cache.passes(&[99998, 25544], &station, start, end, 5.0).await;
+        assert_eq!(passes, station.passes(&iss, start, end, 5.0).unwrap());
+        assert!(!passes.is_empty());
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, 99998);
+        assert!(matches!(failures[0].1.kind(), ErrorKind::Sgp4(_)));
+    }
+
+    #[test]
+    fn test_find_tle_skips_malformed() {
+        let body = "BROKEN
+1 25338U 98030A   24169.93801846  .00000329  00000+0  15393-3 0  9999
+2 25338  98.5680 197.0492 0009520 328.5342  31.5268 14.266054
+ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791
+";
+        assert_eq!(find_tle(body, QueryFormat::Tle, 25544).map(|tle| tle.name), Some("ISS (ZARYA)".to_string()));
+        assert!(find_tle(body, QueryFormat::Tle, 25338).is_none());
+    }
+
+    #[test]
+    fn test_decode_tles_stray_lines() {
+        let body = "ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791
+";
+        let (results, stray_lines) = decode_tles(body, QueryFormat::Tle);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_ok());
+        assert_eq!(stray_lines, vec![StrayLine {line_number: 4, text: body.lines().nth(3).unwrap().to_string()}]);
+        assert!(decode_tles("No GP data found", QueryFormat::Json).0.is_empty());
+    }
+}
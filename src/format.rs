@@ -0,0 +1,362 @@
+use crate::parse::omm::{object_id_from_designator, KVN_COVARIANCE_KEYWORDS, KVN_SPACECRAFT_KEYWORDS};
+use crate::parse::{compute_checksum, encode_alpha5, Omm, TLE};
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt::{Display, Formatter, Write};
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::math::Float;
+
+
+// Describes a TLE field whose value cannot be written into its fixed columns.
+#[derive(PartialEq, Debug, Clone)]
+pub struct FormatError {
+    pub field: &'static str,
+    pub value: String,
+}
+
+impl FormatError {
+    fn new(field: &'static str, value: impl ToString) -> Self {
+        FormatError { field, value: value.to_string() }
+    }
+}
+
+impl Display for FormatError {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result {
+        write!(formatter, "cannot write {} value {} into its TLE columns", self.field, self.value)
+    }
+}
+
+impl core::error::Error for FormatError {}
+
+
+// Formats a TLE as a 3 line element with recomputed checksums. The name line
+// is omitted when the name is empty, giving a 2 line element.
+pub fn format_tle(tle: &TLE) -> Result<String, FormatError> {
+    let (line1, line2) = format_tle_lines(tle)?;
+    if tle.name.is_empty() {
+        Ok(format!("{}\n{}\n", line1, line2))
+    } else {
+        Ok(format!("{}\n{}\n{}\n", tle.name, line1, line2))
+    }
+}
+
+
+// Formats lines 1 and 2 of a TLE, each 69 characters including the checksum.
+pub fn format_tle_lines(tle: &TLE) -> Result<(String, String), FormatError> {
+    let satellite_number = encode_alpha5(tle.satellite_number)
+        .ok_or_else(|| FormatError::new("satellite_number", tle.satellite_number))?;
+    if tle.ephemeris_type > 9 {
+        return Err(FormatError::new("ephemeris_type", tle.ephemeris_type));
+    }
+    if tle.element_number > 9999 {
+        return Err(FormatError::new("element_number", tle.element_number));
+    }
+    // eccentricities just below 1 round up to 10000000, too wide for the columns
+    let eccentricity = (tle.eccentricity * 1e7).round();
+    if !(0.0..1.0).contains(&tle.eccentricity) || eccentricity > 9999999.0 {
+        return Err(FormatError::new("eccentricity", tle.eccentricity));
+    }
+    if tle.revolution_number > 99999 {
+        return Err(FormatError::new("revolution_number", tle.revolution_number));
+    }
+
+    let line1 = format!(
+        "1 {}{} {:<8} {} {} {} {} {} {:>4}",
+        satellite_number,
+        tle.classification,
+        tle.international_designator.map(|designator| designator.to_tle()).unwrap_or_default(),
+        tle.epoch_text().ok_or_else(|| FormatError::new("epoch", tle.epoch))?,
+        format_first_derivative(tle.first_derivative_mean_motion)?,
+        format_decimal_point_assumed(tle.second_derivative_mean_motion)
+            .ok_or_else(|| FormatError::new("second_derivative_mean_motion", tle.second_derivative_mean_motion))?,
+        format_decimal_point_assumed(tle.drag_term)
+            .ok_or_else(|| FormatError::new("drag_term", tle.drag_term))?,
+        tle.ephemeris_type,
+        tle.element_number,
+    );
+    let line2 = format!(
+        "2 {} {} {} {:07} {} {} {}{:>5}",
+        satellite_number,
+        format_angle(tle.inclination, "inclination")?,
+        format_angle(tle.right_ascension, "right_ascension")?,
+        eccentricity as u32,
+        format_angle(tle.argument_of_perigee, "argument_of_perigee")?,
+        format_angle(tle.mean_anomaly, "mean_anomaly")?,
+        format_mean_motion(tle.mean_motion)?,
+        tle.revolution_number,
+    );
+
+    Ok((with_checksum(line1), with_checksum(line2)))
+}
+
+
+// Formats an OMM in key-value notation, writing the header, metadata, mean
+// elements, TLE parameters, spacecraft parameters, covariance and user
+// defined parameters in standard order, with the metadata between
+// META_START and META_STOP.
+// Values are written at full precision so reading the file back with
+// parse_omm_kvn gives the same elements.
+pub fn format_omm_kvn(omm: &Omm) -> Result<String, FormatError> {
+    let tle = &omm.tle;
+
+    let mut kvn = String::new();
+    let _ = writeln!(kvn, "{:<20} = {}", "CCSDS_OMM_VERS", omm.header.version);
+    push_comments(&mut kvn, &omm.header.comments);
+    let _ = writeln!(kvn, "{:<20} = {}", "CREATION_DATE", omm.header.creation_date);
+    let _ = writeln!(kvn, "{:<20} = {}", "ORIGINATOR", omm.header.originator);
+    if let Some(message_id) = &omm.header.message_id {
+        let _ = writeln!(kvn, "{:<20} = {}", "MESSAGE_ID", message_id);
+    }
+    kvn.push('\n');
+
+    kvn.push_str("META_START\n");
+    push_comments(&mut kvn, &omm.metadata.comments);
+    for (keyword, value) in [
+        ("OBJECT_NAME", Some(tle.name.clone())),
+        ("OBJECT_ID", Some(object_id_from_designator(tle.international_designator))),
+        ("CENTER_NAME", Some(omm.metadata.center_name.clone())),
+        ("REF_FRAME", Some(omm.metadata.ref_frame.clone())),
+        ("REF_FRAME_EPOCH", omm.metadata.ref_frame_epoch.clone()),
+        ("TIME_SYSTEM", Some(omm.metadata.time_system.clone())),
+        ("MEAN_ELEMENT_THEORY", Some(omm.metadata.mean_element_theory.clone())),
+    ] {
+        if let Some(value) = value {
+            let _ = writeln!(kvn, "{:<20} = {}", keyword, value);
+        }
+    }
+    kvn.push_str("META_STOP\n\n");
+
+    push_comments(&mut kvn, &omm.data_comments);
+    let elements: [(&str, String, &str); 15] = [
+        ("EPOCH", tle.epoch.format("%Y-%m-%dT%H:%M:%S%.6f").to_string(), ""),
+        ("MEAN_MOTION", tle.mean_motion.to_string(), "rev/day"),
+        ("ECCENTRICITY", tle.eccentricity.to_string(), ""),
+        ("INCLINATION", tle.inclination.to_string(), "deg"),
+        ("RA_OF_ASC_NODE", tle.right_ascension.to_string(), "deg"),
+        ("ARG_OF_PERICENTER", tle.argument_of_perigee.to_string(), "deg"),
+        ("MEAN_ANOMALY", tle.mean_anomaly.to_string(), "deg"),
+        ("EPHEMERIS_TYPE", tle.ephemeris_type.to_string(), ""),
+        ("CLASSIFICATION_TYPE", tle.classification.to_string(), ""),
+        ("NORAD_CAT_ID", tle.satellite_number.to_string(), ""),
+        ("ELEMENT_SET_NO", tle.element_number.to_string(), ""),
+        ("REV_AT_EPOCH", tle.revolution_number.to_string(), ""),
+        ("BSTAR", tle.drag_term.to_string(), "1/ER"),
+        ("MEAN_MOTION_DOT", tle.first_derivative_mean_motion.to_string(), "rev/day**2"),
+        ("MEAN_MOTION_DDOT", tle.second_derivative_mean_motion.to_string(), "rev/day**3"),
+    ];
+    for (index, (keyword, value, units)) in elements.iter().enumerate() {
+        if index == 7 {
+            kvn.push('\n');
+        }
+        let _ = write!(kvn, "{:<20} = {}", keyword, value);
+        if !units.is_empty() {
+            let _ = write!(kvn, " [{}]", units);
+        }
+        kvn.push('\n');
+    }
+
+    for (parameters, keywords) in [
+        (&omm.spacecraft_parameters, &KVN_SPACECRAFT_KEYWORDS[..]),
+        (&omm.covariance, &KVN_COVARIANCE_KEYWORDS[..]),
+    ] {
+        if parameters.is_empty() {
+            continue;
+        }
+        kvn.push('\n');
+        for (keyword, units) in keywords {
+            let Some(value) = parameters.get(*keyword) else {
+                continue;
+            };
+            let _ = write!(kvn, "{:<20} = {}", keyword, value);
+            if !units.is_empty() {
+                let _ = write!(kvn, " [{}]", units);
+            }
+            kvn.push('\n');
+        }
+    }
+
+    if !omm.user_defined.is_empty() {
+        kvn.push('\n');
+        for (parameter, value) in &omm.user_defined {
+            let _ = writeln!(kvn, "{:<20} = {}", format!("USER_DEFINED_{}", parameter), value);
+        }
+    }
+
+    Ok(kvn)
+}
+
+fn push_comments(kvn: &mut String, comments: &[String]) {
+    for comment in comments {
+        let _ = writeln!(kvn, "COMMENT {}", comment);
+    }
+}
+
+
+// Appends the modulo-10 checksum to a 68 character line.
+fn with_checksum(mut line: String) -> String {
+    let checksum = compute_checksum(&line);
+    line.push_str(&checksum.to_string());
+    line
+}
+
+// Formats the first derivative of mean motion with the leading zero dropped (e.g. " .00000950").
+fn format_first_derivative(value: f64) -> Result<String, FormatError> {
+    // values that round up to 1 have no leading zero to drop
+    let digits = format!("{:.8}", value.abs());
+    let fraction = digits
+        .strip_prefix('0')
+        .ok_or_else(|| FormatError::new("first_derivative_mean_motion", value))?;
+    let sign = if value < 0.0 { '-' } else { ' ' };
+    Ok(format!("{}{}", sign, fraction))
+}
+
+// Formats a value in decimal point assumed exponent notation (e.g. " 25302-4").
+fn format_decimal_point_assumed(value: f64) -> Option<String> {
+    if !value.is_finite() {
+        return None;
+    }
+    let sign = if value < 0.0 { '-' } else { ' ' };
+    if value == 0.0 {
+        return Some(format!("{}00000+0", sign));
+    }
+    let mut exponent = value.abs().log10().floor() as i32 + 1;
+    let mut mantissa = (value.abs() / 10f64.powi(exponent) * 1e5).round() as u32;
+    if mantissa >= 100000 {
+        mantissa /= 10;
+        exponent += 1;
+    }
+    if !(-9..=9).contains(&exponent) {
+        return None;
+    }
+    let exponent_sign = if exponent < 0 { '-' } else { '+' };
+    Some(format!("{}{:05}{}{}", sign, mantissa, exponent_sign, exponent.abs()))
+}
+
+// Formats an angle in degrees into 8 columns with 4 decimal places.
+fn format_angle(value: f64, field: &'static str) -> Result<String, FormatError> {
+    if !(0.0..=360.0).contains(&value) {
+        return Err(FormatError::new(field, value));
+    }
+    Ok(format!("{:8.4}", value))
+}
+
+// Formats mean motion in revs per day into 11 columns with 8 decimal places.
+fn format_mean_motion(value: f64) -> Result<String, FormatError> {
+    let text = format!("{:11.8}", value);
+    // values just below 100 round up to 12 characters
+    if !(0.0..100.0).contains(&value) || text.len() > 11 {
+        return Err(FormatError::new("mean_motion", value));
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_tle, split_tle, verify_checksum};
+
+    const TLES: &str = "ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791
+GOES 14
+1 35491U 09033A   24169.87063115 -.00000044  00000+0  00000+0 0  9997
+2 35491   0.3405 104.0766 0003981 351.0753  16.3310  1.00272898 54803
+ARKTIKA-M 1
+1 47719U 21016A   24169.25177733  .00000188  00000+0  00000+0 0  9990
+2 47719  63.1394 151.7679 6941537 268.6991  17.9346  2.00610662 24142
+NOAA 15
+1 25338U 98030A   24169.93801846  .00000329  00000+0  15393-3 0  9999
+2 25338  98.5680 197.0492 0009520 328.5342  31.5268 14.26605440357297
+NEGATIVE DRAG
+1 T1234U 98067A   20045.18587073  .00000950  12345-5 -11606-4 0  9993
+2 T1234 139.6443 242.0161 0004885 264.6060 207.3845 15.49165514212798
+";
+
+    #[test]
+    fn test_format_decimal_point_assumed() {
+        assert_eq!(format_decimal_point_assumed(0.25302e-4).unwrap(), " 25302-4");
+        assert_eq!(format_decimal_point_assumed(-0.11606e-4).unwrap(), "-11606-4");
+        assert_eq!(format_decimal_point_assumed(0.0).unwrap(), " 00000+0");
+        assert_eq!(format_decimal_point_assumed(0.5).unwrap(), " 50000+0");
+        assert_eq!(format_decimal_point_assumed(1e-20), None);
+        assert_eq!(format_decimal_point_assumed(f64::INFINITY), None);
+        assert_eq!(format_decimal_point_assumed(f64::NEG_INFINITY), None);
+        assert_eq!(format_decimal_point_assumed(f64::NAN), None);
+    }
+
+    #[test]
+    fn test_format_tle() {
+        let tle = parse_tle(TLES.lines().take(3).collect::<Vec<&str>>().join("\n").as_str()).unwrap();
+        assert_eq!(
+            format_tle(&tle).unwrap(),
+            "ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000+0  25302-4 0  9999
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791
+"
+        );
+    }
+
+    #[test]
+    fn test_format_limits() {
+        let iss = parse_tle(TLES.lines().take(3).collect::<Vec<&str>>().join("\n").as_str()).unwrap();
+        let format = |change: &dyn Fn(&mut TLE)| {
+            let mut tle = iss.clone();
+            change(&mut tle);
+            format_tle_lines(&tle)
+        };
+
+        assert_eq!(&format(&|tle| tle.eccentricity = 0.99999994).unwrap().1[26..33], "9999999");
+        assert_eq!(format(&|tle| tle.eccentricity = 0.99999995), Err(FormatError::new("eccentricity", 0.99999995)));
+
+        assert_eq!(&format(&|tle| tle.first_derivative_mean_motion = -0.999999994).unwrap().0[33..43], "-.99999999");
+        assert_eq!(
+            format(&|tle| tle.first_derivative_mean_motion = 0.999999995),
+            Err(FormatError::new("first_derivative_mean_motion", 0.999999995))
+        );
+        assert!(format(&|tle| tle.first_derivative_mean_motion = -1.0).is_err());
+
+        assert_eq!(&format(&|tle| tle.revolution_number = 99999).unwrap().1[63..68], "99999");
+        assert_eq!(format(&|tle| tle.revolution_number = 100000), Err(FormatError::new("revolution_number", 100000)));
+
+        assert_eq!(&format(&|tle| tle.mean_motion = 99.999999994).unwrap().1[52..63], "99.99999999");
+        assert_eq!(format(&|tle| tle.mean_motion = 99.999999996), Err(FormatError::new("mean_motion", 99.999999996)));
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        for record in split_tle(TLES.to_string()) {
+            let tle = parse_tle(&record).unwrap();
+            let formatted = format_tle(&tle).unwrap();
+            for line in formatted.lines().skip(1) {
+                assert_eq!(line.len(), 69);
+                assert!(verify_checksum(line));
+            }
+            assert_eq!(parse_tle(&formatted).unwrap(), tle);
+        }
+    }
+
+    #[test]
+    fn test_format_omm_kvn_round_trip() {
+        let omm = crate::parse::parse_omm_kvn(crate::parse::omm::tests::KVN).unwrap();
+        let kvn = format_omm_kvn(&omm).unwrap();
+        assert!(kvn.contains("\nMETA_START\nCOMMENT GOES 14 metadata\nOBJECT_NAME          = GOES 14\n"));
+        assert!(kvn.contains("MEAN_ELEMENT_THEORY  = SGP4\nMETA_STOP\n"));
+        assert!(kvn.contains("OBJECT_ID            = 2009-033A\n"));
+        assert!(kvn.contains("MEAN_MOTION          = 1.00272898 [rev/day]\n"));
+        assert!(kvn.contains("MESSAGE_ID           = GOES14_20240618\n"));
+        assert!(kvn.contains("REF_FRAME_EPOCH      = 2024-06-17T00:00:00\n"));
+        assert!(kvn.contains("MASS                 = 3133 [kg]\nDRAG_COEFF           = 2.2\n"));
+        assert!(kvn.contains("COV_REF_FRAME        = TEME\nCX_X                 = 3.331e-04 [km**2]\n"));
+        assert_eq!(crate::parse::parse_omm_kvn(&kvn).unwrap(), omm);
+
+        for record in split_tle(TLES.to_string()) {
+            let tle = parse_tle(&record).unwrap();
+            let omm = Omm::created_at(tle.clone(), tle.epoch);
+            assert_eq!(omm.header.creation_date, tle.epoch.format("%Y-%m-%dT%H:%M:%S").to_string());
+            let kvn = format_omm_kvn(&omm).unwrap();
+            assert_eq!(crate::parse::parse_omm_kvn(&kvn).unwrap(), omm);
+        }
+    }
+}
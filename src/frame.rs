@@ -0,0 +1,274 @@
+use crate::orbit::WGS84;
+use crate::sgp4::{Sgp4, Sgp4Error, State};
+use chrono::{DateTime, Utc};
+use core::f64::consts::TAU;
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::math::Float;
+
+
+// The Earth's rotation rate in rad/s.
+const EARTH_ROTATION_RATE: f64 = 7.29211514670698e-5;
+const SECONDS_PER_DAY: f64 = 86400.0;
+const ARCSECONDS_PER_RADIAN: f64 = 206264.80624709636;
+// 2000 January 1 12:00 UT as a Unix timestamp.
+const J2000: i64 = 946728000;
+
+// Earth orientation parameters, as published daily in IERS Bulletin A.
+// Without them UT1 is taken as UTC, which is within 0.9 seconds, and the
+// pole as the Earth-fixed z axis, which is within about 15 metres.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct EarthOrientation {
+    // UT1-UTC in seconds.
+    pub dut1: f64,
+    // The pole's x and y coordinates in arcseconds.
+    pub x_pole: f64,
+    pub y_pole: f64,
+}
+
+// A position on (or above) the WGS-84 ellipsoid. Latitude and longitude
+// are in degrees, longitude in (-180, 180], and altitude in km above the
+// ellipsoid.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Geodetic {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+}
+
+// Greenwich mean sidereal time in radians with the IAU 1982 model, the one
+// TEME is defined against.
+pub fn gmst(time: DateTime<Utc>, orientation: Option<EarthOrientation>) -> f64 {
+    let dut1 = orientation.map_or(0.0, |orientation| orientation.dut1);
+    let seconds = (time.timestamp() - J2000) as f64 + time.timestamp_subsec_nanos() as f64 * 1e-9 + dut1;
+    let tut1 = seconds / SECONDS_PER_DAY / 36525.0;
+    let seconds_of_time = -6.2e-6 * tut1 * tut1 * tut1
+        + 0.093104 * tut1 * tut1
+        + (876600.0 * 3600.0 + 8640184.812866) * tut1
+        + 67310.54841;
+    let gmst = (seconds_of_time / 240.0).to_radians() % TAU;
+    if gmst < 0.0 {
+        gmst + TAU
+    } else {
+        gmst
+    }
+}
+
+// Rotates a TEME state, as SGP4 produces, into the Earth-fixed frame: the
+// pseudo Earth-fixed frame without orientation parameters, ITRF with them.
+// The velocity is relative to the rotating Earth.
+pub fn teme_to_ecef(state: &State, time: DateTime<Utc>, orientation: Option<EarthOrientation>) -> State {
+    let gmst = gmst(time, orientation);
+    let position = rotate_z(state.position, -gmst);
+    let velocity = rotate_z(state.velocity, -gmst);
+    let velocity = [
+        velocity[0] + EARTH_ROTATION_RATE * position[1],
+        velocity[1] - EARTH_ROTATION_RATE * position[0],
+        velocity[2],
+    ];
+    State {
+        position: pef_to_itrf(position, orientation),
+        velocity: pef_to_itrf(velocity, orientation),
+    }
+}
+
+// The reverse of teme_to_ecef.
+pub fn ecef_to_teme(state: &State, time: DateTime<Utc>, orientation: Option<EarthOrientation>) -> State {
+    let gmst = gmst(time, orientation);
+    let position = itrf_to_pef(state.position, orientation);
+    let velocity = itrf_to_pef(state.velocity, orientation);
+    let velocity = [
+        velocity[0] - EARTH_ROTATION_RATE * position[1],
+        velocity[1] + EARTH_ROTATION_RATE * position[0],
+        velocity[2],
+    ];
+    State {
+        position: rotate_z(position, gmst),
+        velocity: rotate_z(velocity, gmst),
+    }
+}
+
+// Converts an Earth-fixed position in km to geodetic coordinates, iterating
+// on the latitude until it changes by less than 1e-12 radians.
+pub fn ecef_to_geodetic(position: [f64; 3]) -> Geodetic {
+    let [x, y, z] = position;
+    let (a, f) = (WGS84.radius, WGS84.flattening);
+    let e2 = f * (2.0 - f);
+    let rho = (x * x + y * y).sqrt();
+
+    let mut latitude = z.atan2(rho);
+    let mut n = a;
+    for _ in 0..10 {
+        let sin_latitude = latitude.sin();
+        n = a / (1.0 - e2 * sin_latitude * sin_latitude).sqrt();
+        let previous = latitude;
+        latitude = (z + n * e2 * sin_latitude).atan2(rho);
+        if (latitude - previous).abs() < 1e-12 {
+            break;
+        }
+    }
+
+    // rho / cos(latitude) loses precision towards the poles
+    let altitude = if latitude.cos().abs() > 0.1 {
+        rho / latitude.cos() - n
+    } else {
+        z / latitude.sin() - n * (1.0 - e2)
+    };
+    // atan2 gives -180 when y is -0.0, which is outside (-180, 180]
+    let longitude = y.atan2(x).to_degrees();
+    Geodetic {
+        latitude: latitude.to_degrees(),
+        longitude: if longitude <= -180.0 {180.0} else {longitude},
+        altitude,
+    }
+}
+
+// Converts geodetic coordinates to an Earth-fixed position in km.
+pub fn geodetic_to_ecef(geodetic: Geodetic) -> [f64; 3] {
+    let (a, f) = (WGS84.radius, WGS84.flattening);
+    let e2 = f * (2.0 - f);
+    let (sin_latitude, cos_latitude) = (geodetic.latitude.to_radians().sin(), geodetic.latitude.to_radians().cos());
+    let (sin_longitude, cos_longitude) = (geodetic.longitude.to_radians().sin(), geodetic.longitude.to_radians().cos());
+    let n = a / (1.0 - e2 * sin_latitude * sin_latitude).sqrt();
+    [
+        (n + geodetic.altitude) * cos_latitude * cos_longitude,
+        (n + geodetic.altitude) * cos_latitude * sin_longitude,
+        (n * (1.0 - e2) + geodetic.altitude) * sin_latitude,
+    ]
+}
+
+fn rotate_z(vector: [f64; 3], angle: f64) -> [f64; 3] {
+    let (sin, cos) = (angle.sin(), angle.cos());
+    [cos * vector[0] - sin * vector[1], sin * vector[0] + cos * vector[1], vector[2]]
+}
+
+// The polar motion rotation of the IAU 1980 theory, W = R1(yp) R2(xp)
+// transposed, from Vallado's polarm.
+fn polar_motion(orientation: Option<EarthOrientation>) -> Option<[[f64; 3]; 3]> {
+    let orientation = orientation?;
+    let (xp, yp) = (orientation.x_pole / ARCSECONDS_PER_RADIAN, orientation.y_pole / ARCSECONDS_PER_RADIAN);
+    let (sin_xp, cos_xp) = (xp.sin(), xp.cos());
+    let (sin_yp, cos_yp) = (yp.sin(), yp.cos());
+    Some([
+        [cos_xp, 0.0, -sin_xp],
+        [sin_xp * sin_yp, cos_yp, cos_xp * sin_yp],
+        [sin_xp * cos_yp, -sin_yp, cos_xp * cos_yp],
+    ])
+}
+
+fn itrf_to_pef(vector: [f64; 3], orientation: Option<EarthOrientation>) -> [f64; 3] {
+    match polar_motion(orientation) {
+        Some(matrix) => matrix.map(|row| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2]),
+        None => vector,
+    }
+}
+
+fn pef_to_itrf(vector: [f64; 3], orientation: Option<EarthOrientation>) -> [f64; 3] {
+    match polar_motion(orientation) {
+        Some(matrix) => [0, 1, 2].map(|i| matrix[0][i] * vector[0] + matrix[1][i] * vector[1] + matrix[2][i] * vector[2]),
+        None => vector,
+    }
+}
+
+
+impl Sgp4 {
+
+    // Propagates to a time and rotates the state into the Earth-fixed frame.
+    pub fn propagate_ecef(&self, time: DateTime<Utc>, orientation: Option<EarthOrientation>) -> Result<State, Sgp4Error> {
+        Ok(teme_to_ecef(&self.propagate_to(time)?, time, orientation))
+    }
+
+    // Propagates to a time and returns the geodetic position beneath (and
+    // above) the satellite.
+    pub fn propagate_geodetic(&self, time: DateTime<Utc>, orientation: Option<EarthOrientation>) -> Result<Geodetic, Sgp4Error> {
+        Ok(ecef_to_geodetic(self.propagate_ecef(time, orientation)?.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+
+    fn assert_close(actual: [f64; 3], expected: [f64; 3], tolerance: f64) {
+        for i in 0..3 {
+            assert!((actual[i] - expected[i]).abs() <= tolerance, "{:?} is not within {} of {:?}", actual, tolerance, expected);
+        }
+    }
+
+    // Vallado's TEME example from "Coordinate Frames of the US Space
+    // Object Catalogs" (AIAA 2008-6770), 2004-04-06 07:51:28.386009 UTC.
+    // The published values went through the full IAU-76/FK5 reduction, so
+    // only agree to about a centimetre.
+    #[test]
+    fn test_teme_to_itrf() {
+        let time = NaiveDate::from_ymd_opt(2004, 4, 6).unwrap().and_hms_micro_opt(7, 51, 28, 386009).unwrap().and_utc();
+        let orientation = EarthOrientation { dut1: -0.4399619, x_pole: -0.140682, y_pole: 0.333309 };
+        let teme = State {
+            position: [5094.18016210, 6127.64465950, 6380.34453270],
+            velocity: [-4.746131487, 0.785818041, 5.531931288],
+        };
+
+        let itrf = teme_to_ecef(&teme, time, Some(orientation));
+        assert_close(itrf.position, [-1033.4793830, 7901.2952754, 6380.3565958], 1e-5);
+        assert_close(itrf.velocity, [-3.225636520, -2.872451450, 5.531924446], 1e-7);
+
+        let round_trip = ecef_to_teme(&itrf, time, Some(orientation));
+        assert_close(round_trip.position, teme.position, 1e-8);
+        assert_close(round_trip.velocity, teme.velocity, 1e-11);
+
+        // polar motion moves the pseudo Earth-fixed frame by metres
+        let pef = teme_to_ecef(&teme, time, Some(EarthOrientation { dut1: orientation.dut1, ..Default::default() }));
+        assert_close(pef.position, itrf.position, 0.02);
+    }
+
+    #[test]
+    fn test_gmst() {
+        // Vallado example 3-5: 1992-08-20 12:14:00 UT1 is 152.578787886 degrees
+        let time = NaiveDate::from_ymd_opt(1992, 8, 20).unwrap().and_hms_opt(12, 14, 0).unwrap().and_utc();
+        assert!((gmst(time, None).to_degrees() - 152.578787886).abs() < 1e-7);
+        let one_sidereal_day = Duration::nanoseconds(86_164_090_530_833);
+        assert!((gmst(time + one_sidereal_day, None) - gmst(time, None)).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_geodetic() {
+        // Vallado example 3-3
+        let geodetic = ecef_to_geodetic([6524.834, 6862.875, 6448.296]);
+        assert!((geodetic.latitude - 34.352496).abs() < 1e-6, "{:?}", geodetic);
+        assert!((geodetic.longitude - 46.4464).abs() < 1e-4, "{:?}", geodetic);
+        assert!((geodetic.altitude - 5085.22).abs() < 1e-2, "{:?}", geodetic);
+
+        for (latitude, longitude, altitude) in [(0.0, 0.0, 0.0), (51.5, -0.1, 0.05), (-89.9999, 179.0, 400.0), (90.0, 0.0, 35786.0)] {
+            let geodetic = Geodetic { latitude, longitude, altitude };
+            let round_trip = ecef_to_geodetic(geodetic_to_ecef(geodetic));
+            assert_close(
+                [round_trip.latitude, round_trip.longitude, round_trip.altitude],
+                [latitude, longitude, altitude],
+                1e-9,
+            );
+        }
+        assert_close(geodetic_to_ecef(Geodetic { latitude: 90.0, longitude: 0.0, altitude: 0.0 }), [0.0, 0.0, 6356.752314245], 1e-9);
+
+        for y in [0.0, -0.0] {
+            assert_eq!(ecef_to_geodetic([-7000.0, y, 0.0]).longitude, 180.0);
+        }
+    }
+
+    #[test]
+    fn test_propagate_geodetic() {
+        let iss = crate::parse::parse_tle("ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap();
+        let sgp4 = Sgp4::new(&iss).unwrap();
+        for minutes in (0..200).step_by(5) {
+            let time = iss.epoch + Duration::minutes(minutes);
+            let geodetic = sgp4.propagate_geodetic(time, None).unwrap();
+            assert!(geodetic.latitude.abs() < 51.9, "{:?}", geodetic);
+            assert!((400.0..440.0).contains(&geodetic.altitude), "{:?}", geodetic);
+
+            let ecef = sgp4.propagate_ecef(time, None).unwrap();
+            assert_close(geodetic_to_ecef(geodetic), ecef.position, 1e-8);
+        }
+    }
+}
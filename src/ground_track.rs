@@ -0,0 +1,239 @@
+use crate::frame::Geodetic;
+use crate::parse::TLE;
+use crate::sgp4::{Sgp4, Sgp4Error};
+use alloc::vec::Vec;
+use chrono::{DateTime, Duration, Utc};
+use core::fmt::{Display, Formatter};
+use serde_json::{json, Value};
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::math::Float;
+
+
+// The sub-satellite point at a time, with the satellite's altitude.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct TrackPoint {
+    pub time: DateTime<Utc>,
+    pub position: Geodetic,
+}
+
+// The path of the sub-satellite point, split into segments where it
+// crosses the antimeridian. Each crossing ends one segment at longitude
+// 180 (or -180) and starts the next at the other, with the latitude,
+// altitude and time interpolated between the neighbouring samples, so the
+// segments can be drawn as lines on a map without wrapping around it.
+#[derive(PartialEq, Debug, Clone)]
+pub struct GroundTrack {
+    pub satellite_number: u32,
+    pub segments: Vec<Vec<TrackPoint>>,
+}
+
+// Why a ground track couldn't be sampled.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum GroundTrackError {
+    // The step between samples is zero or negative.
+    NonPositiveStep(Duration),
+    // The number of orbits is zero, negative or not finite.
+    InvalidOrbits(f64),
+    // The end of the track is outside the range chrono can represent.
+    EndOutOfRange,
+    Sgp4(Sgp4Error),
+}
+
+impl Display for GroundTrackError {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result {
+        match self {
+            GroundTrackError::NonPositiveStep(step) => write!(formatter, "ground track step {} is not positive", step),
+            GroundTrackError::InvalidOrbits(orbits) => write!(formatter, "number of orbits {} is not positive", orbits),
+            GroundTrackError::EndOutOfRange => write!(formatter, "the end of the ground track is out of range"),
+            GroundTrackError::Sgp4(e) => write!(formatter, "{}", e),
+        }
+    }
+}
+
+impl core::error::Error for GroundTrackError {}
+
+impl From<Sgp4Error> for GroundTrackError {
+    fn from(e: Sgp4Error) -> Self {
+        GroundTrackError::Sgp4(e)
+    }
+}
+
+impl GroundTrack {
+
+    // Samples the track every step from start until end, including end
+    // even when it isn't a whole number of steps away. The step must be
+    // positive.
+    pub fn between(tle: &TLE, start: DateTime<Utc>, end: DateTime<Utc>, step: Duration) -> Result<Self, GroundTrackError> {
+        if step <= Duration::zero() {
+            return Err(GroundTrackError::NonPositiveStep(step));
+        }
+        let sgp4 = Sgp4::new(tle)?;
+        let mut track = GroundTrack {satellite_number: tle.satellite_number, segments: Vec::new()};
+        let mut time = start;
+        while time <= end {
+            track.push(TrackPoint {time, position: sgp4.propagate_geodetic(time, None)?});
+            if time == end {
+                break;
+            }
+            time = (time + step).min(end);
+        }
+        Ok(track)
+    }
+
+    // Samples the track over a number of orbits, using the TLE's mean
+    // motion for the period. The number of orbits must be positive.
+    pub fn orbits(tle: &TLE, start: DateTime<Utc>, orbits: f64, step: Duration) -> Result<Self, GroundTrackError> {
+        if !(orbits.is_finite() && orbits > 0.0) {
+            return Err(GroundTrackError::InvalidOrbits(orbits));
+        }
+        let end = Duration::try_milliseconds((orbits * tle.period() * 60000.0).round() as i64)
+            .and_then(|length| start.checked_add_signed(length))
+            .ok_or(GroundTrackError::EndOutOfRange)?;
+        GroundTrack::between(tle, start, end, step)
+    }
+
+    // All the points in time order, including the interpolated ones at
+    // the antimeridian.
+    pub fn points(&self) -> impl Iterator<Item = &TrackPoint> {
+        self.segments.iter().flatten()
+    }
+
+    // The track as a GeoJSON geometry, a LineString if it never crosses
+    // the antimeridian and a MultiLineString if it does. Positions are
+    // [longitude, latitude] as RFC 7946 orders them. A track with no points,
+    // from an end before its start, is an empty LineString.
+    pub fn to_geojson(&self) -> Value {
+        let lines: Vec<Vec<[f64; 2]>> = self
+            .segments
+            .iter()
+            .map(|segment| segment.iter().map(|point| [point.position.longitude, point.position.latitude]).collect())
+            .collect();
+        match lines.as_slice() {
+            [] => json!({"type": "LineString", "coordinates": []}),
+            [line] => json!({"type": "LineString", "coordinates": line}),
+            _ => json!({"type": "MultiLineString", "coordinates": lines}),
+        }
+    }
+
+    fn push(&mut self, point: TrackPoint) {
+        let Some(previous) = self.segments.last().and_then(|segment| segment.last()).copied() else {
+            self.segments.push(Vec::from([point]));
+            return;
+        };
+        let change = point.position.longitude - previous.position.longitude;
+        if change.abs() > 180.0 {
+            // heading east the longitude jumps from +180 to -180, and west
+            // the other way
+            let edge = if change < 0.0 {180.0} else {-180.0};
+            let unwrapped = point.position.longitude + 2.0 * edge;
+            let fraction = (edge - previous.position.longitude) / (unwrapped - previous.position.longitude);
+            let crossing = interpolate(&previous, &point, fraction);
+            self.segments.last_mut().unwrap().push(TrackPoint {position: Geodetic {longitude: edge, ..crossing.position}, ..crossing});
+            self.segments.push(Vec::from([TrackPoint {position: Geodetic {longitude: -edge, ..crossing.position}, ..crossing}]));
+        }
+        self.segments.last_mut().unwrap().push(point);
+    }
+}
+
+fn interpolate(from: &TrackPoint, to: &TrackPoint, fraction: f64) -> TrackPoint {
+    let nanoseconds = (to.time - from.time).num_nanoseconds().unwrap_or(i64::MAX) as f64;
+    TrackPoint {
+        time: from.time + Duration::nanoseconds((nanoseconds * fraction).round() as i64),
+        position: Geodetic {
+            latitude: from.position.latitude + (to.position.latitude - from.position.latitude) * fraction,
+            longitude: from.position.longitude,
+            altitude: from.position.altitude + (to.position.altitude - from.position.altitude) * fraction,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_tle;
+
+    fn iss() -> TLE {
+        parse_tle("ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap()
+    }
+
+    #[test]
+    fn test_ground_track() {
+        let iss = iss();
+        let track = GroundTrack::orbits(&iss, iss.epoch, 3.0, Duration::seconds(30)).unwrap();
+        assert_eq!(track.satellite_number, 25544);
+        // the ISS crosses the antimeridian once an orbit
+        assert!((3..=4).contains(&track.segments.len()), "{}", track.segments.len());
+
+        let points: Vec<_> = track.points().collect();
+        assert_eq!(points[0].time, iss.epoch);
+        assert_eq!(points.last().unwrap().time, iss.epoch + Duration::milliseconds((3.0 * iss.period() * 60000.0).round() as i64));
+        assert!(points.windows(2).all(|pair| pair[0].time <= pair[1].time));
+        assert!(points.iter().all(|point| point.position.latitude.abs() < 51.9 && (400.0..440.0).contains(&point.position.altitude)));
+
+        for (i, segment) in track.segments.iter().enumerate() {
+            assert!(segment.windows(2).all(|pair| (pair[1].position.longitude - pair[0].position.longitude).abs() < 5.0));
+            // the ISS orbits eastwards, so leaves at +180 and arrives at -180
+            if i > 0 {
+                assert_eq!(segment[0].position.longitude, -180.0);
+                assert_eq!(segment[0].position, Geodetic {longitude: -180.0, ..track.segments[i - 1].last().unwrap().position});
+                assert_eq!(segment[0].time, track.segments[i - 1].last().unwrap().time);
+            }
+        }
+
+        // the interpolated crossing lies on the track
+        let crossing = track.segments[1][0];
+        let sgp4 = Sgp4::new(&iss).unwrap();
+        let actual = sgp4.propagate_geodetic(crossing.time, None).unwrap();
+        assert!(actual.longitude.abs() > 179.99, "{:?}", actual);
+        assert!((actual.latitude - crossing.position.latitude).abs() < 0.01, "{:?} {:?}", actual, crossing);
+    }
+
+    #[test]
+    fn test_geojson() {
+        let iss = iss();
+        let track = GroundTrack::orbits(&iss, iss.epoch, 2.0, Duration::minutes(1)).unwrap();
+        let geojson = track.to_geojson();
+        assert_eq!(geojson["type"], "MultiLineString");
+        let lines = geojson["coordinates"].as_array().unwrap();
+        assert_eq!(lines.len(), track.segments.len());
+        let first = &track.segments[0][0].position;
+        assert_eq!(lines[0][0], json!([first.longitude, first.latitude]));
+
+        // just after crossing the antimeridian
+        let start = track.segments[1][1].time;
+        let short = GroundTrack::between(&iss, start, start + Duration::minutes(10), Duration::minutes(1)).unwrap();
+        assert_eq!(short.segments.len(), 1);
+        assert_eq!(short.points().count(), 11);
+        let geojson = short.to_geojson();
+        assert_eq!(geojson["type"], "LineString");
+        assert_eq!(geojson["coordinates"].as_array().unwrap().len(), 11);
+    }
+
+    #[test]
+    fn test_errors() {
+        let iss = iss();
+        for step in [Duration::zero(), Duration::seconds(-30)] {
+            assert_eq!(GroundTrack::between(&iss, iss.epoch, iss.epoch + Duration::hours(1), step), Err(GroundTrackError::NonPositiveStep(step)));
+            assert_eq!(GroundTrack::orbits(&iss, iss.epoch, 1.0, step), Err(GroundTrackError::NonPositiveStep(step)));
+        }
+
+        for orbits in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let result = GroundTrack::orbits(&iss, iss.epoch, orbits, Duration::minutes(1));
+            assert!(matches!(result, Err(GroundTrackError::InvalidOrbits(value)) if value.to_bits() == orbits.to_bits()), "{:?}", result);
+        }
+        assert_eq!(GroundTrack::orbits(&iss, iss.epoch, 1e12, Duration::minutes(1)), Err(GroundTrackError::EndOutOfRange));
+
+        let empty = GroundTrack::between(&iss, iss.epoch, iss.epoch - Duration::hours(1), Duration::minutes(1)).unwrap();
+        assert!(empty.segments.is_empty());
+        assert_eq!(empty.to_geojson(), json!({"type": "LineString", "coordinates": []}));
+
+        let mut stopped = iss.clone();
+        stopped.mean_motion = 0.0;
+        let result = GroundTrack::between(&stopped, iss.epoch, iss.epoch + Duration::hours(1), Duration::minutes(1));
+        assert!(matches!(result, Err(GroundTrackError::Sgp4(_))), "{:?}", result);
+    }
+}
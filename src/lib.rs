@@ -1,2 +1,20 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(feature = "alloc"))]
+compile_error!("the `alloc` feature is required, enable it or `std`");
+
+extern crate alloc;
+
+#[cfg(feature = "fetch")]
 pub mod fetch;
-pub mod parse;
\ No newline at end of file
+pub mod format;
+pub mod frame;
+pub mod ground_track;
+mod math;
+pub mod orbit;
+pub mod parse;
+pub mod pass;
+pub mod sgp4;
+pub mod station;
+#[cfg(feature = "std")]
+pub mod stream;
@@ -1,13 +1,14 @@
-mod fetch;
-mod parse;
-use fetch::{load_tle_cache, Cache};
+use src::fetch::{self, load_tle_cache, Cache};
 
 
 #[tokio::main]
 async fn main() -> Result<(), fetch::Error> {
     println!("Initalising Cache.");
-    let mut cache: Cache = load_tle_cache(Some("./output/cache.json".to_string())).await.expect("Failed to load cache.");
+    let (mut cache, skipped): (Cache, _) = load_tle_cache(Some("./output/cache.json".to_string())).await.expect("Failed to load cache.");
     println!("Cache initalised with length.");
+    for record in skipped {
+        println!("Skipped {}", record);
+    }
 
     match cache.get_tle(25544).await {
         Ok(tle) => println!("{}", tle),
@@ -19,7 +20,7 @@ async fn main() -> Result<(), fetch::Error> {
         Err(e) => println!("Error occurred whilst getting TLE from cache: {}", e),
     }
 
-    cache.to_file("./output/cache.json".to_string());
+    cache.to_file("./output/cache.json".to_string())?;
 
     Ok(())
 }
@@ -0,0 +1,86 @@
+// The f64 functions core doesn't provide, for builds without std. Modules
+// that need them import the trait only when std is missing, so std builds
+// keep using the inherent methods:
+//
+//     #[cfg(not(any(feature = "std", test)))]
+//     #[allow(unused_imports)]
+//     use crate::math::Float;
+//
+// The inherent methods are still visible when a dependency links std (as
+// the dev-dependencies do), leaving the trait unused, hence the allows.
+#![allow(dead_code)]
+
+pub(crate) trait Float {
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn cbrt(self) -> Self;
+    fn cos(self) -> Self;
+    fn log10(self) -> Self;
+    fn abs(self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+impl Float for f64 {
+    fn floor(self) -> Self {
+        libm::floor(self)
+    }
+
+    fn ceil(self) -> Self {
+        libm::ceil(self)
+    }
+
+    fn round(self) -> Self {
+        libm::round(self)
+    }
+
+    fn powi(self, n: i32) -> Self {
+        libm::pow(self, n as f64)
+    }
+
+    fn cbrt(self) -> Self {
+        libm::cbrt(self)
+    }
+
+    fn cos(self) -> Self {
+        libm::cos(self)
+    }
+
+    fn log10(self) -> Self {
+        libm::log10(self)
+    }
+
+    fn abs(self) -> Self {
+        libm::fabs(self)
+    }
+
+    fn powf(self, n: Self) -> Self {
+        libm::pow(self, n)
+    }
+
+    fn sqrt(self) -> Self {
+        libm::sqrt(self)
+    }
+
+    fn sin(self) -> Self {
+        libm::sin(self)
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        libm::atan2(self, other)
+    }
+
+    fn rem_euclid(self, rhs: Self) -> Self {
+        let remainder = self % rhs;
+        if remainder < 0.0 {
+            remainder + rhs.abs()
+        } else {
+            remainder
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::parse::TLE;
+use core::f64::consts::PI;
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::math::Float;
+
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+const MINUTES_PER_DAY: f64 = 1440.0;
+
+// Earth gravity model constants. Distances are in km and mu in km^3/s^2.
+// J2, J3 and J4 are the unnormalised zonal harmonic coefficients.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct EarthConstants {
+    pub mu: f64,
+    pub radius: f64,
+    pub j2: f64,
+    pub j3: f64,
+    pub j4: f64,
+    pub flattening: f64,
+}
+
+// WGS-72, the model TLEs are generated with and SGP4 expects.
+pub const WGS72: EarthConstants = EarthConstants {
+    mu: 398600.8,
+    radius: 6378.135,
+    j2: 0.001082616,
+    j3: -0.00000253881,
+    j4: -0.00000165597,
+    flattening: 1.0 / 298.26,
+};
+
+// WGS-84, as used by GPS and most geodetic work. mu is the value
+// Vallado uses with SGP4 rather than the full precision 398600.4418.
+pub const WGS84: EarthConstants = EarthConstants {
+    mu: 398600.5,
+    radius: 6378.137,
+    j2: 0.00108262998905,
+    j3: -0.00000253215306,
+    j4: -0.00000161098761,
+    flattening: 1.0 / 298.257223563,
+};
+
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum EarthModel {
+    #[default]
+    Wgs72,
+    Wgs84,
+}
+
+impl EarthModel {
+    pub fn constants(self) -> EarthConstants {
+        match self {
+            EarthModel::Wgs72 => WGS72,
+            EarthModel::Wgs84 => WGS84,
+        }
+    }
+}
+
+
+// Quantities derived from the mean elements of a TLE. These use two body
+// motion with the secular J2 terms added for the precession rates; they are
+// the usual back of the envelope values, not a propagation.
+impl TLE {
+
+    // Mean motion in radians per second.
+    pub fn mean_motion_rad_per_sec(&self) -> f64 {
+        self.mean_motion * 2.0 * PI / SECONDS_PER_DAY
+    }
+
+    // Orbital period in minutes.
+    pub fn period(&self) -> f64 {
+        MINUTES_PER_DAY / self.mean_motion
+    }
+
+    // Semi-major axis in km, from Kepler's third law.
+    pub fn semi_major_axis(&self, model: EarthModel) -> f64 {
+        let n = self.mean_motion_rad_per_sec();
+        (model.constants().mu / (n * n)).cbrt()
+    }
+
+    // Apogee altitude above the equatorial radius, in km.
+    pub fn apogee_altitude(&self, model: EarthModel) -> f64 {
+        self.semi_major_axis(model) * (1.0 + self.eccentricity) - model.constants().radius
+    }
+
+    // Perigee altitude above the equatorial radius, in km.
+    pub fn perigee_altitude(&self, model: EarthModel) -> f64 {
+        self.semi_major_axis(model) * (1.0 - self.eccentricity) - model.constants().radius
+    }
+
+    // Specific orbital energy (-mu / 2a) in km^2/s^2.
+    pub fn specific_energy(&self, model: EarthModel) -> f64 {
+        -model.constants().mu / (2.0 * self.semi_major_axis(model))
+    }
+
+    // Secular rate of change of the right ascension of the ascending node
+    // due to J2, in degrees per day. Negative (westward) for prograde orbits.
+    pub fn nodal_precession_rate(&self, model: EarthModel) -> f64 {
+        let inclination = self.inclination.to_radians();
+        (-1.5 * self.j2_rate_factor(model) * inclination.cos()).to_degrees() * SECONDS_PER_DAY
+    }
+
+    // Secular rate of change of the argument of perigee due to J2, in
+    // degrees per day. Zero at the critical inclination of 63.4 degrees.
+    pub fn argument_of_perigee_drift(&self, model: EarthModel) -> f64 {
+        let inclination = self.inclination.to_radians();
+        let cos_squared = inclination.cos().powi(2);
+        (0.75 * self.j2_rate_factor(model) * (5.0 * cos_squared - 1.0)).to_degrees() * SECONDS_PER_DAY
+    }
+
+    // n * J2 * (Re / p)^2, shared by the J2 secular rates, in radians per second.
+    fn j2_rate_factor(&self, model: EarthModel) -> f64 {
+        let constants = model.constants();
+        let semi_latus_rectum = self.semi_major_axis(model) * (1.0 - self.eccentricity * self.eccentricity);
+        self.mean_motion_rad_per_sec() * constants.j2 * (constants.radius / semi_latus_rectum).powi(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_tle;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() <= tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+    }
+
+    #[test]
+    fn test_iss_orbit() {
+        let iss = parse_tle("ISS (ZARYA)
+        1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+        2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap();
+
+        assert_close(iss.period(), 92.954, 0.001);
+        assert_close(iss.semi_major_axis(EarthModel::Wgs72), 6797.0, 1.0);
+        assert_close(iss.perigee_altitude(EarthModel::Wgs72), 415.5, 1.0);
+        assert_close(iss.apogee_altitude(EarthModel::Wgs72), 422.2, 1.0);
+        assert_close(iss.specific_energy(EarthModel::Wgs72), -29.32, 0.01);
+        // the ISS node regresses about 5 degrees a day
+        assert_close(iss.nodal_precession_rate(EarthModel::Wgs72), -5.0, 0.1);
+        assert_close(iss.argument_of_perigee_drift(EarthModel::Wgs72), 3.7, 0.1);
+
+        let difference = iss.semi_major_axis(EarthModel::Wgs84) - iss.semi_major_axis(EarthModel::Wgs72);
+        assert!(difference < 0.0 && difference > -0.01);
+    }
+
+    #[test]
+    fn test_critical_inclination() {
+        let mut molniya = parse_tle("MOLNIYA 1-91
+        1 25485U 98054A   24169.50833113  .00000117  00000+0  00000+0 0  9998
+        2 25485  64.1300 214.7240 6804350 284.7004  14.8786  2.36478960189543").unwrap_or_else(|e| panic!("{}", e));
+        molniya.inclination = (1.0f64 / 5.0).sqrt().acos().to_degrees();
+        assert_close(molniya.argument_of_perigee_drift(EarthModel::Wgs84), 0.0, 1e-12);
+        assert!(molniya.nodal_precession_rate(EarthModel::Wgs84) < 0.0);
+    }
+}
@@ -1,16 +1,43 @@
-use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::fmt::{Display, Formatter};
-
-
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+use chrono::{DateTime, Datelike, Duration, NaiveDate, SecondsFormat, Timelike, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+use core::str::FromStr;
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::math::Float;
+
+mod borrowed;
+mod fields;
+mod lenient;
+pub mod omm;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod validate;
+pub use borrowed::{tle_refs, TleRef, TleRefs};
+pub use fields::{Classification, FieldError, InternationalDesignator};
+pub use lenient::{parse_tle_lenient, Correction, LenientTle};
+pub use omm::{parse_omm_json, parse_omm_kvn, Omm, OmmError, OmmHeader, OmmMetadata};
+#[cfg(feature = "std")]
+pub use omm::{parse_omm_csv, parse_omm_xml};
+#[cfg(feature = "parallel")]
+pub use parallel::parse_tles_parallel;
+pub use validate::{Finding, Severity};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TLE {
     pub name: String,
     pub satellite_number: u32,
-    pub classification: char,
-    pub international_designator: String,
-    pub epoch: i64,
-    pub date_time: String,
+    pub classification: Classification,
+    // None when the designator columns are blank.
+    #[serde(default, deserialize_with = "fields::deserialize_optional_designator")]
+    pub international_designator: Option<InternationalDesignator>,
+    #[serde(with = "epoch_format")]
+    pub epoch: DateTime<Utc>,
     pub first_derivative_mean_motion: f64,
     pub second_derivative_mean_motion: f64,
     pub drag_term: f64,
@@ -23,22 +50,88 @@ pub struct TLE {
     pub mean_anomaly: f64,
     pub mean_motion: f64,
     pub revolution_number: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checksum_mismatches: Vec<ChecksumMismatch>,
     last_updated_epoch: i64
 }
 
+impl TLE {
+
+    // The epoch in the form written in TLEs (YYDDD.DDDDDDDD). Epochs read
+    // from a TLE are stored exactly, so this gives back the original text.
+    // Returns None for years a 2 digit TLE year can't represent.
+    pub fn epoch_text(&self) -> Option<String> {
+        format_tle_epoch(&self.epoch)
+    }
+}
+
+// Serializes the epoch as RFC3339 with microseconds, which holds every TLE
+// epoch exactly. Caches written before the epoch was a DateTime stored it
+// as whole seconds, so integer timestamps are still accepted.
+mod epoch_format {
+    use super::*;
+    use serde::de::Error;
+
+    pub fn serialize<S: Serializer>(epoch: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&epoch.to_rfc3339_opts(SecondsFormat::Micros, true))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Epoch {
+            Text(String),
+            Timestamp(i64),
+        }
+
+        match Epoch::deserialize(deserializer)? {
+            Epoch::Text(text) => DateTime::parse_from_rfc3339(&text)
+                .map(|epoch| epoch.with_timezone(&Utc))
+                .map_err(D::Error::custom),
+            Epoch::Timestamp(timestamp) => DateTime::from_timestamp(timestamp, 0)
+                .ok_or_else(|| D::Error::custom(format!("epoch timestamp {} out of range", timestamp))),
+        }
+    }
+}
+
+// Two TLEs are equal when their elements are, regardless of when each was parsed.
+impl PartialEq for TLE {
+
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.satellite_number == other.satellite_number
+            && self.classification == other.classification
+            && self.international_designator == other.international_designator
+            && self.epoch == other.epoch
+            && self.first_derivative_mean_motion == other.first_derivative_mean_motion
+            && self.second_derivative_mean_motion == other.second_derivative_mean_motion
+            && self.drag_term == other.drag_term
+            && self.ephemeris_type == other.ephemeris_type
+            && self.element_number == other.element_number
+            && self.inclination == other.inclination
+            && self.right_ascension == other.right_ascension
+            && self.eccentricity == other.eccentricity
+            && self.argument_of_perigee == other.argument_of_perigee
+            && self.mean_anomaly == other.mean_anomaly
+            && self.mean_motion == other.mean_motion
+            && self.revolution_number == other.revolution_number
+            && self.checksum_mismatches == other.checksum_mismatches
+    }
+}
+
 impl Display for TLE {
 
-    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::std::fmt::Result { 
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result { 
         write!(
             formatter, 
             "{}\nSatellite #: {}\nClassification: {}\nInternational Designator: {}\nElement #: {}\nEpoch: {}\nEpoch (ISO8601) {}\nMean Motion: {}\nFirst Derivative Mean Motion: {}\nSecond Derivative Mean Motion: {}\nDrag Term: {}\nInclination: {}\nRight Angle of Ascencion: {}\nEccentricity: {}\nArgument of Perigee: {}\nMean Anomaly: {}\nRevolution #: {}", 
             self.name, 
             self.satellite_number, 
             self.classification,
-            self.international_designator,
+            self.international_designator.map(|designator| designator.to_string()).unwrap_or_default(),
             self.element_number,
-            self.epoch, 
-            self.date_time,
+            self.epoch_text().unwrap_or_default(),
+            self.epoch.to_rfc3339_opts(SecondsFormat::Micros, true),
             self.mean_motion, 
             self.first_derivative_mean_motion,
             self.second_derivative_mean_motion, 
@@ -55,176 +148,429 @@ impl Display for TLE {
 }
 
 
-// Splits a large string into groups of 3.
-pub fn split_tle(tles: String) -> Vec<String> {
-    let mut lines: Vec<String> = Vec::new();
-    let mut grouped = String::new();
-
-    for (index, line) in tles.lines().enumerate() {
-        grouped.push_str(line);
-        grouped.push_str("\n");
-        if (index + 1) % 3 == 0 {
-            lines.push(grouped.clone());
-            grouped.clear();
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ParseErrorKind {
+    // A name line, line 1 or line 2 is absent from the record.
+    MissingLine,
+    // The line ends before the field's columns.
+    MissingField,
+    // The field is present but its text could not be parsed.
+    InvalidValue,
+    // The checksum in column 69 does not match the line's contents.
+    ChecksumMismatch,
+}
+
+// Describes why a TLE record could not be parsed: which line (0 for the
+// name line), the 1-based inclusive column range, the field and its text.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub line: usize,
+    pub columns: (usize, usize),
+    pub field: &'static str,
+    pub text: String,
+}
+
+impl ParseError {
+    fn missing_line(line: usize) -> Self {
+        ParseError {
+            kind: ParseErrorKind::MissingLine,
+            line,
+            columns: (1, 69),
+            field: "line",
+            text: String::new(),
         }
     }
 
-    return lines;
-}
-
-
-// Takes a 3 line element as a string and parses it into a TLE struct.
-pub fn parse_tle(tle: &String) -> TLE {
-    let mut lines = tle.lines();
-
-    let name = lines.next().expect("Expected TLE Name Line").trim().to_string();
-    let line1 = lines.next().expect("Expected TLE Line 1").trim();
-    let line2 = lines.next().expect("Expected TLE Line 2").trim();
-    let epoch: DateTime<Utc> = get_epoch_from_tle(line1[18..=32].trim().to_string());
-
-    let parsed_tle: TLE = TLE {
-        name: name,
-        satellite_number: line1[2..7]
-            .trim()
-            .parse::<u32>()
-            .expect("Could not parse satellite_number."),
-        classification: line1[7..8].trim()
-            .chars()
-            .next()
-            .expect("Could not parse classification."),
-        international_designator: line1[9..17].trim().to_string(),
-        epoch: epoch.timestamp(),
-        date_time: epoch.to_rfc3339(),
-        first_derivative_mean_motion: line1[34..43]
-            .trim()
-            .parse::<f64>()
-            .expect("Could not parse first_derivative_mean_motion."),
-        second_derivative_mean_motion: parse_decimal_point_assumed(line1[45..52].trim().to_string()),
-        drag_term: parse_decimal_point_assumed(line1[54..61].trim().to_string()),
-        ephemeris_type: line1[62..63].trim()
-            .parse::<u32>()
-            .expect("Could not parse ephemeris_type."),
-        element_number: line1[65..68].trim()
-            .parse::<u32>()
-            .expect("Could not parse element_number."),
-        inclination: line2[9..16].trim()
-            .parse::<f64>()
-            .expect("Could not parse inclination"),
-        right_ascension: line2[17..25].trim()
-            .parse::<f64>()
-            .expect("Could not parse right_ascencion."),
-        eccentricity: parse_decimal_point_assumed(line2[26..33].trim().to_string()),
-        argument_of_perigee: line2[34..42].trim()
-            .parse::<f64>()
-            .expect("Could not parse argument_of_perigee."),
-        mean_anomaly: line2[42..51].trim()
-            .parse::<f64>()
-            .expect("Could not parse mean_anomaly."),
-        mean_motion: line2[52..63].trim()
-            .parse::<f64>()
-            .expect("Could not parse mean_motion."),
-        revolution_number: line2[63..68].trim()
-            .parse::<u32>()
-            .expect("Could not parse revolution_number."),
-        last_updated_epoch: Utc::now().timestamp()
-    };
-    return parsed_tle;
+    fn invalid(line: usize, columns: Range<usize>, field: &'static str, text: &str) -> Self {
+        ParseError {
+            kind: ParseErrorKind::InvalidValue,
+            line,
+            columns: (columns.start + 1, columns.end),
+            field,
+            text: text.to_string(),
+        }
+    }
 }
 
+impl Display for ParseError {
 
-// Parses a string into a utc chrono::DateTime object
-fn get_epoch_from_tle(tle_epoch: String) -> DateTime<Utc> {
-    println!("{}", tle_epoch );
-    // get year from first 2 chars
-    let mut epoch_year: i32 = tle_epoch[0..2]
-        .to_string()
-        .parse::<i32>()
-        .expect("Could not parse epoch year.");
-    if epoch_year < 57 {
-        epoch_year = 2000 + epoch_year
-    } else {
-        epoch_year = 1900 + epoch_year;
-    }
-    // get decimal day from remainder of string
-    let decimal_day: String = tle_epoch[2..].to_string();
-
-    // get the full num of days
-    let full_day: Vec<&str> = decimal_day.split_terminator(".").collect();
-
-    // get fractional day
-    let mut day_fraction: f64 = (".".to_owned() + full_day[1])
-        .parse::<f64>()
-        .expect("Could not parse day fraction.");
-
-    // calc hours minutes, seconds milliseconds
-    let hours: f64 = (day_fraction * 24.0).floor();
-    day_fraction -= hours / 24.0;
-    let minutes: f64 = (day_fraction * 24.0 * 60.0).floor();
-    day_fraction -= minutes / (24.0 * 60.0);
-    let seconds: f64 = (day_fraction * 24.0 * 60.0 * 60.0).floor();
-    day_fraction -= seconds / (24.0 * 60.0 * 60.0);
-    let milliseconds: f64 = ((day_fraction * 24.0 * 60.0 * 60.0 * 1000.0) + 0.5).floor();
-
-    // create date time and correct time on jan 1st
-    let date: NaiveDate = NaiveDate::from_ymd_opt(epoch_year, 1, 1).unwrap();
-    let time: NaiveTime = NaiveTime::from_hms_milli_opt(
-        hours as u32,
-        minutes as u32,
-        seconds as u32,
-        milliseconds as u32,
-    )
-    .unwrap();
-    let mut date_time = NaiveDateTime::new(date, time);
-
-    // add days - 1 to date_time to get correct day, remove 1 because the days start from 0 and our datetime starts from 1.
-    let days = full_day[0]
-        .to_string()
-        .parse::<i64>()
-        .expect("Could not parse full days.");
-    date_time += Duration::days(days - 1);
-
-    return date_time.and_utc();
-}
-
-// Parses a decimal point assumed string into a float.
-fn parse_decimal_point_assumed(input: String) -> f64 {
-    if input.contains('+') || input.contains('-') && !input.starts_with('-') || input.matches('-').count() == 2 {
-        let exp_index: usize;
-        if let Some(index) = input.rfind('+') {
-            exp_index = index;
-        } else {
-            exp_index = input.rfind('-').unwrap();
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result {
+        let reason = match self.kind {
+            ParseErrorKind::MissingLine => "missing line",
+            ParseErrorKind::MissingField => "line too short for",
+            ParseErrorKind::InvalidValue => "could not parse",
+            ParseErrorKind::ChecksumMismatch => "mismatched",
+        };
+        write!(
+            formatter,
+            "line {}, columns {}-{}: {} {} {:?}",
+            self.line,
+            self.columns.0,
+            self.columns.1,
+            reason,
+            self.field,
+            self.text
+        )
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+
+// How parse_tle_with_checksum treats the modulo-10 checksum in column 69.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum ChecksumMode {
+    // Reject the record with a ParseErrorKind::ChecksumMismatch error.
+    Strict,
+    // Accept the record but record the mismatch in TLE::checksum_mismatches.
+    #[default]
+    Warn,
+    // Don't check the checksum at all.
+    Off,
+}
+
+// A line whose checksum did not match, recorded when parsing in ChecksumMode::Warn.
+// `found` is None when column 69 is missing or not a digit.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub struct ChecksumMismatch {
+    pub line: usize,
+    pub expected: u8,
+    pub found: Option<u8>,
+}
+
+// Computes the modulo-10 checksum of a TLE line: the sum of all digits in
+// columns 1-68, with each minus sign counting as 1.
+pub fn compute_checksum(line: &str) -> u8 {
+    let sum: u32 = line
+        .chars()
+        .take(68)
+        .map(|c| match c {
+            '0'..='9' => c as u32 - '0' as u32,
+            '-' => 1,
+            _ => 0,
+        })
+        .sum();
+    (sum % 10) as u8
+}
+
+// Checks the checksum digit in column 69 against the line's contents.
+pub fn verify_checksum(line: &str) -> bool {
+    checksum_digit(line) == Some(compute_checksum(line))
+}
+
+// The current time, used to stamp when a TLE was parsed. Without std there
+// is no clock, so the Unix epoch is used instead.
+#[cfg(feature = "std")]
+fn now() -> DateTime<Utc> {
+    Utc::now()
+}
+
+#[cfg(not(feature = "std"))]
+fn now() -> DateTime<Utc> {
+    DateTime::UNIX_EPOCH
+}
+
+// Reads the checksum digit from column 69.
+fn checksum_digit(line: &str) -> Option<u8> {
+    line.get(68..69)?.parse::<u8>().ok()
+}
+
+
+// A line that could not be placed in any record when splitting.
+// `line_number` is 1-based within the input.
+#[derive(PartialEq, Debug, Clone)]
+pub struct StrayLine {
+    pub line_number: usize,
+    pub text: String,
+}
+
+// The records found by split_tle_records along with any lines left over.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct SplitTle {
+    pub records: Vec<String>,
+    pub stray_lines: Vec<StrayLine>,
+}
+
+// Splits a large string into records, dropping any stray lines.
+pub fn split_tle(tles: String) -> Vec<String> {
+    split_tle_records(&tles).records
+}
+
+
+// Splits a string of 2LE and/or 3LE records into individual records.
+// Records are found by their `1 ` and `2 ` line markers, optionally preceded
+// by a (possibly `0 ` prefixed) name line. Records without a name line are
+// returned with an empty first line so every record has three lines. Blank
+// lines are ignored and lines that don't fit into a record are reported.
+pub fn split_tle_records(tles: &str) -> SplitTle {
+    let mut split = SplitTle::default();
+    let mut splitter = RecordSplitter::new();
+
+    for line in tles.lines() {
+        splitter.push_line(line);
+    }
+    splitter.finish();
+
+    while let Some(item) = splitter.next_record() {
+        match item {
+            Ok(record) => split.records.push(record),
+            Err(stray) => split.stray_lines.push(stray),
         }
+    }
+    split.stray_lines.sort_by_key(|line| line.line_number);
+    split
+}
 
-        let base;
-        if input.starts_with('-') {
-            base = format!("-0.{}", &input[1..exp_index])
-                .parse::<f64>()
-                .expect("Could not parse base.");
-        } else {
-            base = format!("0.{}", &input[0..exp_index])
-                .parse::<f64>()
-                .expect("Could not parse base.");
+
+// Groups lines into records one line at a time, using the same rules as
+// split_tle_records, so records can be split from input that is read
+// incrementally. Completed records and stray lines are queued in the order
+// they are found and taken with next_record. The lines are held as `L`,
+// owned Strings here and borrowed &str for tle_refs, which shares the
+// grouping without copying.
+#[derive(Debug, Default)]
+pub struct RecordSplitter<L = String> {
+    line_number: usize,
+    name: Option<(usize, L)>,
+    line1: Option<(usize, L)>,
+    ready: VecDeque<Grouped<L>>,
+}
+
+// A record's name (if it had one), line 1 and line 2, or a stray line and
+// its 1-based line number.
+pub(crate) type Grouped<L> = Result<(Option<L>, L, L), (usize, L)>;
+
+impl RecordSplitter {
+    pub fn new() -> Self {
+        RecordSplitter::default()
+    }
+
+    // Adds the next line of input.
+    pub fn push_line(&mut self, raw_line: &str) {
+        self.push(raw_line.trim().to_string());
+    }
+
+    // Takes the next completed record or stray line, if any.
+    pub fn next_record(&mut self) -> Option<Result<String, StrayLine>> {
+        Some(match self.next_grouped()? {
+            Ok((name, line1, line2)) => Ok(format!("{}\n{}\n{}\n", name.unwrap_or_default(), line1, line2)),
+            Err((line_number, text)) => Err(StrayLine { line_number, text }),
+        })
+    }
+}
+
+impl<L: AsRef<str>> RecordSplitter<L> {
+
+    // Adds the next line of input, already trimmed.
+    pub(crate) fn push(&mut self, text: L) {
+        self.line_number += 1;
+        if text.as_ref().is_empty() {
+            return;
         }
-        let exponent;
-        if input[exp_index..].starts_with('+') {
-            exponent = input[(exp_index + 1)..].parse::<f64>()
-            .expect("Could not parse exponent.")
+        let line = (self.line_number, text);
+
+        if line.1.as_ref().starts_with("1 ") {
+            if let Some(previous) = self.line1.replace(line) {
+                self.ready.push_back(Err(previous));
+            }
+        } else if line.1.as_ref().starts_with("2 ") {
+            match self.line1.take() {
+                Some((_, first)) => {
+                    let name = self.name.take().map(|(_, name)| name);
+                    self.ready.push_back(Ok((name, first, line.1)));
+                }
+                None => self.ready.push_back(Err(line)),
+            }
         } else {
-            exponent = input[exp_index..].parse::<f64>()
-            .expect("Could not parse exponent.")
+            if let Some(previous) = self.line1.take() {
+                self.ready.push_back(Err(previous));
+            }
+            if let Some(previous) = self.name.replace(line) {
+                self.ready.push_back(Err(previous));
+            }
         }
-        // 15 dp is the general accuracy of a f64.
-        return format!("{:.15}",base * 10f64.powf(exponent)).parse::<f64>().expect("Could not parse rounded decimal point assumed.")
-    } else if input.contains('-') {
-        return format!("-0.{}", input)
-            .parse::<f64>()
-            .expect("Could not parse decimal point assumed value at parse_decimal_point_assumed")
+    }
+
+    // Marks the end of input, reporting any incomplete record as stray lines.
+    pub fn finish(&mut self) {
+        let mut leftover: Vec<(usize, L)> = self.name.take().into_iter().chain(self.line1.take()).collect();
+        leftover.sort_by_key(|(line_number, _)| *line_number);
+        self.ready.extend(leftover.into_iter().map(Err));
+    }
+
+    pub(crate) fn next_grouped(&mut self) -> Option<Grouped<L>> {
+        self.ready.pop_front()
+    }
+}
+
+
+// Takes a 3 line (or 2 line, without a name) element as a string and parses
+// it into a TLE struct, flagging (but accepting) lines with a bad checksum.
+pub fn parse_tle(tle: &str) -> Result<TLE, ParseError> {
+    parse_tle_with_checksum(tle, ChecksumMode::default())
+}
+
+
+// Parses a 2 or 3 line element, handling checksums as specified by `mode`.
+pub fn parse_tle_with_checksum(tle: &str, mode: ChecksumMode) -> Result<TLE, ParseError> {
+    TleRef::new(tle)?.to_tle_with_checksum(mode)
+}
+
+
+// Splits a large string into records and parses each one, keeping
+// the result for every record so bad ones can be reported and skipped.
+pub fn parse_tles(tles: String) -> Vec<Result<TLE, ParseError>> {
+    split_tle(tles).iter().map(|tle| parse_tle(tle)).collect()
+}
+
+
+// Returns the trimmed text of a fixed-column field, or an error if the line is too short.
+fn field<'a>(line: &'a str, line_number: usize, columns: Range<usize>, name: &'static str) -> Result<&'a str, ParseError> {
+    match line.get(columns.clone()) {
+        Some(text) => Ok(text.trim()),
+        None => Err(ParseError {
+            kind: ParseErrorKind::MissingField,
+            line: line_number,
+            columns: (columns.start + 1, columns.end),
+            field: name,
+            text: line.get(columns.start..).unwrap_or("").to_string(),
+        }),
+    }
+}
+
+// Parses a fixed-column field using its FromStr implementation.
+fn parse_field<T: FromStr>(line: &str, line_number: usize, columns: Range<usize>, name: &'static str) -> Result<T, ParseError> {
+    let text = field(line, line_number, columns.clone(), name)?;
+    text.parse::<T>()
+        .map_err(|_| ParseError::invalid(line_number, columns, name, text))
+}
+
+// Parses a fixed-column field written in decimal point assumed notation.
+fn parse_assumed_field(line: &str, line_number: usize, columns: Range<usize>, name: &'static str) -> Result<f64, ParseError> {
+    let text = field(line, line_number, columns.clone(), name)?;
+    parse_decimal_point_assumed(text)
+        .ok_or_else(|| ParseError::invalid(line_number, columns, name, text))
+}
+
+
+// Parses the catalog number field, which may be in Alpha-5 form.
+fn parse_satellite_number(line: &str, line_number: usize, columns: Range<usize>) -> Result<u32, ParseError> {
+    let text = field(line, line_number, columns.clone(), "satellite_number")?;
+    decode_alpha5(text)
+        .ok_or_else(|| ParseError::invalid(line_number, columns, "satellite_number", text))
+}
+
+
+// Alpha-5 replaces the leading digit of a 5 character catalog number with
+// a letter to represent ids from 100000 to 339999. I and O are skipped to
+// avoid confusion with 1 and 0, so A = 10, H = 17, J = 18, P = 23, Z = 33.
+const ALPHA5_LETTERS: &str = "ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+// Decodes a catalog number in either plain numeric or Alpha-5 form.
+pub fn decode_alpha5(text: &str) -> Option<u32> {
+    let mut chars = text.chars();
+    let first = chars.next()?;
+    if first.is_ascii_digit() {
+        return text.parse::<u32>().ok();
+    }
+    let rest = chars.as_str();
+    if rest.len() != 4 || !rest.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let prefix = ALPHA5_LETTERS.find(first)? as u32 + 10;
+    Some(prefix * 10000 + rest.parse::<u32>().ok()?)
+}
+
+// Encodes a catalog number into the 5 character form used in TLEs, using
+// Alpha-5 for ids over 99999. Returns None for ids over 339999.
+pub fn encode_alpha5(number: u32) -> Option<String> {
+    if number < 100000 {
+        return Some(format!("{:05}", number));
+    }
+    let letter = ALPHA5_LETTERS.chars().nth((number / 10000 - 10) as usize)?;
+    Some(format!("{}{:04}", letter, number % 10000))
+}
+
+
+const NANOSECONDS_PER_DAY: u128 = 86_400_000_000_000;
+
+// Parses a TLE epoch (YYDDD.DDDDDDDD) into a utc chrono::DateTime object
+// without allocating. The fractional day is converted with integer math, so
+// the 8 digit fraction of a TLE (units of 864 microseconds) is kept exactly.
+fn get_epoch_from_tle(tle_epoch: &str) -> Option<DateTime<Utc>> {
+    // get year from first 2 chars
+    let mut epoch_year: i32 = tle_epoch.get(0..2)?.parse::<i32>().ok()?;
+    if epoch_year < 57 {
+        epoch_year += 2000;
     } else {
-        return format!("0.{}", input)
-            .parse::<f64>()
-            .expect("Could not parse decimal point assumed value at parse_decimal_point_assumed")
+        epoch_year += 1900;
     }
+    // split the decimal day from remainder of string into full and fractional days
+    let (full_day, fraction) = tle_epoch.get(2..)?.split_once('.')?;
+    let days = full_day.parse::<i64>().ok()?;
+    let nanoseconds = day_fraction_nanoseconds(fraction)?;
+
+    // start at midnight on jan 1st and add days - 1, because the day of year starts from 1.
+    let date_time = NaiveDate::from_ymd_opt(epoch_year, 1, 1)?.and_hms_opt(0, 0, 0)?
+        + Duration::days(days - 1)
+        + Duration::nanoseconds(nanoseconds);
+
+    Some(date_time.and_utc())
+}
+
+// Converts the digits after the decimal point of a fractional day into
+// nanoseconds, rounding to the nearest nanosecond.
+fn day_fraction_nanoseconds(digits: &str) -> Option<i64> {
+    if digits.is_empty() || digits.len() > 18 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let scale = 10u128.pow(digits.len() as u32);
+    let numerator = digits.parse::<u128>().ok()? * NANOSECONDS_PER_DAY;
+    Some(((numerator + scale / 2) / scale) as i64)
+}
+
+// Formats an epoch as a 2 digit year and fractional day of year
+// (YYDDD.DDDDDDDD), rounding to the nearest 1e-8 of a day. This is the
+// inverse of get_epoch_from_tle for every epoch it produces.
+fn format_tle_epoch(epoch: &DateTime<Utc>) -> Option<String> {
+    const UNIT: u128 = NANOSECONDS_PER_DAY / 100_000_000;
+    // round by shifting half a unit forward, so a round up past midnight also moves the day
+    let rounded = *epoch + Duration::nanoseconds((UNIT / 2) as i64);
+    if !(1957..2057).contains(&rounded.year()) {
+        return None;
+    }
+    let nanoseconds = rounded.num_seconds_from_midnight() as u128 * 1_000_000_000
+        + rounded.nanosecond().min(999_999_999) as u128;
+    Some(format!("{:02}{:03}.{:08}", rounded.year() % 100, rounded.ordinal(), nanoseconds / UNIT))
+}
+
+// Parses a decimal point assumed string, with an optional sign and
+// exponent (e.g. "-36258-4" is -0.36258e-4), into a float without
+// allocating. The mantissa digits are scaled by an exact power of ten
+// so the result is the closest float to the written value.
+fn parse_decimal_point_assumed(input: &str) -> Option<f64> {
+    let (negative, unsigned) = match input.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, input.strip_prefix('+').unwrap_or(input)),
+    };
+    let (digits, exponent) = unsigned.split_at(unsigned.find(['+', '-']).unwrap_or(unsigned.len()));
+    if digits.is_empty() || digits.len() > 15 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let exponent: i32 = if exponent.is_empty() { 0 } else { exponent.parse::<i32>().ok()? };
+    if !(-30..=30).contains(&exponent) {
+        return None;
+    }
+
+    let mantissa = digits.parse::<u64>().ok()? as f64;
+    let scale = exponent - digits.len() as i32;
+    let value = if scale >= 0 {
+        mantissa * 10f64.powi(scale)
+    } else {
+        mantissa / 10f64.powi(-scale)
+    };
+    Some(if negative { -value } else { value })
 }
 
 #[cfg(test)]
@@ -233,16 +579,55 @@ mod tests {
 
     #[test]
     fn test_epoch_parser() {
-        assert_eq!(1718748281, get_epoch_from_tle("24170.91992694".to_string()).timestamp())
+        assert_eq!(1718748281, get_epoch_from_tle("24170.91992694").unwrap().timestamp());
+
+        let epoch = get_epoch_from_tle("24170.91992694").unwrap();
+        assert_eq!(epoch.timestamp_subsec_micros(), 687616);
+        assert_eq!(format_tle_epoch(&epoch).unwrap(), "24170.91992694");
+
+        // every 8 digit fraction survives the round trip, including the last one before midnight
+        for text in ["57001.00000000", "56366.99999999", "00060.50000000", "20045.18587073"] {
+            assert_eq!(format_tle_epoch(&get_epoch_from_tle(text).unwrap()).unwrap(), text);
+        }
+        // shorter fractions are the same instant, written back with 8 digits
+        assert_eq!(get_epoch_from_tle("20045.5"), get_epoch_from_tle("20045.50000000"));
+        // an epoch within half a unit of midnight on new year's eve rounds into the next year
+        let late = get_epoch_from_tle("23365.99999999").unwrap() + Duration::microseconds(500);
+        assert_eq!(format_tle_epoch(&late).unwrap(), "24001.00000000");
+    }
+
+    #[test]
+    fn test_epoch_serde() {
+        let tle = parse_tle("ISS (ZARYA)
+        1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+        2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap();
+
+        let json = serde_json::to_value(&tle).unwrap();
+        assert_eq!(json["epoch"], "2020-02-14T04:27:39.231072Z");
+        let read_back: TLE = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(read_back, tle);
+        assert_eq!(read_back.epoch_text().unwrap(), "20045.18587073");
+
+        // caches written with a seconds timestamp and untyped designator still load
+        let mut legacy = json;
+        legacy["epoch"] = 1581654459.into();
+        legacy["date_time"] = "2020-02-14T04:27:39.231+00:00".into();
+        legacy["international_designator"] = "98067A".into();
+        let read_back: TLE = serde_json::from_value(legacy.clone()).unwrap();
+        assert_eq!(read_back.epoch.timestamp(), 1581654459);
+        assert_eq!(read_back.international_designator, tle.international_designator);
+        legacy["international_designator"] = "".into();
+        let read_back: TLE = serde_json::from_value(legacy).unwrap();
+        assert_eq!(read_back.international_designator, None);
     }
 
     #[test]
     fn test_decimal_point_parser() {
-        assert_eq!(0.00014141 as f64, parse_decimal_point_assumed("14141-3".to_string()));
+        assert_eq!(0.00014141_f64, parse_decimal_point_assumed("14141-3").unwrap());
 
-        assert_eq!(parse_decimal_point_assumed("00000-0".to_string()), 0.0 as f64);
+        assert_eq!(parse_decimal_point_assumed("00000-0").unwrap(), 0.0_f64);
 
-        assert_eq!(parse_decimal_point_assumed("-36258-4".to_string()), -0.36258e-4);
+        assert_eq!(parse_decimal_point_assumed("-36258-4").unwrap(), -0.36258e-4);
     }
 
     #[test]
@@ -252,14 +637,16 @@ mod tests {
         1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
         2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791";
         
-        let tle = parse_tle(&raw_tle.to_string());
+        let tle = parse_tle(raw_tle).unwrap();
 
         assert_eq!(tle.name, "ISS (ZARYA)".to_string());
         assert_eq!(tle.satellite_number, 25544);
-        assert_eq!(tle.classification, 'U');
-        assert_eq!(tle.international_designator, "98067A".to_string());
-        assert_eq!(tle.epoch, 1581654459);
-        assert_eq!(tle.date_time, "2020-02-14T04:27:39.231+00:00".to_string());
+        assert_eq!(tle.classification, Classification::Unclassified);
+        let designator = tle.international_designator.unwrap();
+        assert_eq!((designator.launch_year(), designator.launch_number(), designator.piece()), (1998, 67, "A"));
+        assert_eq!(tle.epoch.timestamp(), 1581654459);
+        assert_eq!(tle.epoch.to_rfc3339(), "2020-02-14T04:27:39.231072+00:00".to_string());
+        assert_eq!(tle.epoch_text().unwrap(), "20045.18587073");
         assert_eq!(tle.first_derivative_mean_motion, 0.00000950);
         assert_eq!(tle.second_derivative_mean_motion, 0.0);
         assert_eq!(tle.drag_term, 0.25302e-4);
@@ -272,18 +659,19 @@ mod tests {
         assert_eq!(tle.mean_anomaly, 207.3845);
         assert_eq!(tle.mean_motion, 15.49165514);
         assert_eq!(tle.revolution_number, 21279);
+        assert!(tle.checksum_mismatches.is_empty());
 
         raw_tle = "GRUS-1A
         1 43890U 18111Q   20044.88470557  .00000320  00000-0  36258-4 0  9993
         2 43890  97.7009 312.6237 0003899   7.8254 352.3026 14.92889838 61757";
         
-        let tle = parse_tle(&raw_tle.to_string());
+        let tle = parse_tle(raw_tle).unwrap();
 
         assert_eq!(tle.name, "GRUS-1A");
         assert_eq!(tle.satellite_number, 43890);
-        assert_eq!(tle.classification, 'U');
-        assert_eq!(tle.international_designator, "18111Q");
-        assert_eq!(tle.epoch, 1581628438);
+        assert_eq!(tle.classification, Classification::Unclassified);
+        assert_eq!(tle.international_designator.unwrap().to_string(), "2018-111Q");
+        assert_eq!(tle.epoch.timestamp(), 1581628438);
         assert_eq!(tle.first_derivative_mean_motion, 0.00000320);
         assert_eq!(tle.second_derivative_mean_motion, 0.0);
         assert_eq!(tle.drag_term, 0.36258e-4);
@@ -298,4 +686,132 @@ mod tests {
         assert_eq!(tle.mean_motion, 14.92889838);
         assert_eq!(tle.revolution_number, 6175);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_tle_parse_errors() {
+        let err = parse_tle("ISS (ZARYA)\n1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MissingLine);
+        assert_eq!(err.line, 2);
+
+        let err = parse_tle("ISS (ZARYA)
+        1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+        2 25544  51.6443 242.0161 0004885 264.6060 207.3845").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MissingField);
+        assert_eq!(err.line, 2);
+        assert_eq!(err.columns, (53, 63));
+        assert_eq!(err.field, "mean_motion");
+
+        let err = parse_tle("ISS (ZARYA)
+        1 25544U 98067A   20045.18587073  .000X0950  00000-0  25302-4 0  9990
+        2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidValue);
+        assert_eq!(err.line, 1);
+        assert_eq!(err.columns, (34, 43));
+        assert_eq!(err.field, "first_derivative_mean_motion");
+        assert_eq!(err.text, ".000X0950");
+        assert_eq!(err.to_string(), "line 1, columns 34-43: could not parse first_derivative_mean_motion \".000X0950\"");
+
+        let err = parse_tle("1 25544X 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+        2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap_err();
+        assert_eq!((err.kind, err.columns, err.field), (ParseErrorKind::InvalidValue, (8, 8), "classification"));
+
+        let err = parse_tle("1 25544U 98O67A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+        2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap_err();
+        assert_eq!((err.kind, err.field, err.text.as_str()), (ParseErrorKind::InvalidValue, "international_designator", "98O67A"));
+
+        // a blank designator is allowed
+        let tle = parse_tle("1 25544U          20045.18587073  .00000950  00000-0  25302-4 0  9990
+        2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap();
+        assert_eq!(tle.international_designator, None);
+    }
+
+    #[test]
+    fn test_checksum() {
+        let line1 = "1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990";
+        let line2 = "2 43890  97.7009 312.6237 0003899   7.8254 352.3026 14.92889838 61757";
+        assert_eq!(compute_checksum(line1), 0);
+        assert_eq!(compute_checksum(line2), 7);
+        assert!(verify_checksum(line1));
+        assert!(verify_checksum(line2));
+        assert!(!verify_checksum("1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9991"));
+        assert!(!verify_checksum("1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  999"));
+
+        let raw_tle = "ISS (ZARYA)
+        1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9993
+        2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791";
+
+        let err = parse_tle_with_checksum(raw_tle, ChecksumMode::Strict).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::ChecksumMismatch);
+        assert_eq!(err.line, 1);
+        assert_eq!(err.columns, (69, 69));
+
+        let tle = parse_tle_with_checksum(raw_tle, ChecksumMode::Warn).unwrap();
+        assert_eq!(tle.checksum_mismatches, vec![ChecksumMismatch { line: 1, expected: 0, found: Some(3) }]);
+
+        let tle = parse_tle_with_checksum(raw_tle, ChecksumMode::Off).unwrap();
+        assert!(tle.checksum_mismatches.is_empty());
+    }
+
+    #[test]
+    fn test_split_tle() {
+        let raw_tles = "0 ISS (ZARYA)\r
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990\r
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791\r
+\r
+1 43890U 18111Q   20044.88470557  .00000320  00000-0  36258-4 0  9993
+2 43890  97.7009 312.6237 0003899   7.8254 352.3026 14.92889838 61757
+STRAY NAME
+NOAA 15
+1 25338U 98030A   24169.93801846  .00000329  00000+0  15393-3 0  9999
+1 28054U 03048A   24169.92781536  .00000136  00000+0  95636-4 0  9999
+2 28054  99.0199 176.4030 0008260  55.9567   4.7973 14.14038707 66403
+2 28654  98.8743 246.8749 0015146  64.1200 296.1532 14.13233021983273";
+
+        let split = split_tle_records(raw_tles);
+        assert_eq!(split.records.len(), 3);
+        assert!(split.records[0].starts_with("0 ISS (ZARYA)\n1 25544U"));
+        assert!(split.records[1].starts_with("\n1 43890U"));
+        assert!(split.records[2].starts_with("NOAA 15\n1 28054U"));
+        assert_eq!(
+            split.stray_lines.iter().map(|line| line.line_number).collect::<Vec<usize>>(),
+            vec![7, 9, 12]
+        );
+
+        let tles: Vec<TLE> = split.records.iter().map(|tle| parse_tle(tle).unwrap()).collect();
+        assert_eq!(tles[0].name, "ISS (ZARYA)");
+        assert_eq!(tles[1].name, "");
+        assert_eq!(tles[1].satellite_number, 43890);
+        assert_eq!(tles[2].satellite_number, 28054);
+
+        let tle = parse_tle("1 43890U 18111Q   20044.88470557  .00000320  00000-0  36258-4 0  9993
+        2 43890  97.7009 312.6237 0003899   7.8254 352.3026 14.92889838 61757").unwrap();
+        assert_eq!(tle.name, "");
+        assert_eq!(tle.satellite_number, 43890);
+    }
+
+    #[test]
+    fn test_alpha5() {
+        assert_eq!(decode_alpha5("25544"), Some(25544));
+        assert_eq!(decode_alpha5("A0000"), Some(100000));
+        assert_eq!(decode_alpha5("A0001"), Some(100001));
+        assert_eq!(decode_alpha5("H9999"), Some(179999));
+        assert_eq!(decode_alpha5("J0000"), Some(180000));
+        assert_eq!(decode_alpha5("T1234"), Some(271234));
+        assert_eq!(decode_alpha5("Z9999"), Some(339999));
+        assert_eq!(decode_alpha5("I0000"), None);
+        assert_eq!(decode_alpha5("O0000"), None);
+        assert_eq!(decode_alpha5("A12"), None);
+
+        assert_eq!(encode_alpha5(25544), Some("25544".to_string()));
+        assert_eq!(encode_alpha5(5), Some("00005".to_string()));
+        assert_eq!(encode_alpha5(100001), Some("A0001".to_string()));
+        assert_eq!(encode_alpha5(271234), Some("T1234".to_string()));
+        assert_eq!(encode_alpha5(339999), Some("Z9999".to_string()));
+        assert_eq!(encode_alpha5(340000), None);
+
+        let tle = parse_tle("ALPHA5 TEST
+        1 T1234U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+        2 T1234  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap();
+        assert_eq!(tle.satellite_number, 271234);
+    }
+}
@@ -0,0 +1,285 @@
+use super::{
+    checksum_digit, compute_checksum, field, get_epoch_from_tle, now, parse_assumed_field, parse_field,
+    parse_satellite_number, ChecksumMismatch, ChecksumMode, Classification, InternationalDesignator, ParseError,
+    ParseErrorKind, RecordSplitter, StrayLine, TLE,
+};
+use chrono::{DateTime, Utc};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+
+// A TLE record borrowed from the input buffer. Creating one only finds the
+// name, line 1 and line 2; each field is decoded when its method is called,
+// without allocating, so callers that need a handful of fields from a large
+// archive don't pay for the rest.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct TleRef<'a> {
+    name: &'a str,
+    line1: &'a str,
+    line2: &'a str,
+}
+
+impl<'a> TleRef<'a> {
+    // Finds the lines of a 3 line (or 2 line, without a name) element.
+    pub fn new(tle: &'a str) -> Result<Self, ParseError> {
+        let mut lines = tle.lines().map(str::trim).skip_while(|line| line.is_empty()).peekable();
+
+        let name = match lines.peek() {
+            Some(line) if line.starts_with("1 ") => "",
+            _ => lines.next().ok_or_else(|| ParseError::missing_line(0))?,
+        };
+        let line1 = lines.next().ok_or_else(|| ParseError::missing_line(1))?;
+        let line2 = lines.next().ok_or_else(|| ParseError::missing_line(2))?;
+        Ok(TleRef::from_lines(name, line1, line2))
+    }
+
+    // Wraps lines that have already been split, trimming them and any `0 ` name prefix.
+    pub fn from_lines(name: &'a str, line1: &'a str, line2: &'a str) -> Self {
+        let name = name.trim();
+        TleRef {
+            name: name.strip_prefix("0 ").unwrap_or(name).trim(),
+            line1: line1.trim(),
+            line2: line2.trim(),
+        }
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub fn line1(&self) -> &'a str {
+        self.line1
+    }
+
+    pub fn line2(&self) -> &'a str {
+        self.line2
+    }
+
+    pub fn satellite_number(&self) -> Result<u32, ParseError> {
+        parse_satellite_number(self.line1, 1, 2..7)
+    }
+
+    pub fn classification(&self) -> Result<Classification, ParseError> {
+        parse_field(self.line1, 1, 7..8, "classification")
+    }
+
+    // The designator as written in the TLE (98067A), empty when blank.
+    pub fn international_designator_text(&self) -> Result<&'a str, ParseError> {
+        field(self.line1, 1, 9..17, "international_designator")
+    }
+
+    pub fn international_designator(&self) -> Result<Option<InternationalDesignator>, ParseError> {
+        match self.international_designator_text()? {
+            "" => Ok(None),
+            text => InternationalDesignator::from_tle(text)
+                .map(Some)
+                .ok_or_else(|| ParseError::invalid(1, 9..17, "international_designator", text)),
+        }
+    }
+
+    // The epoch as written in the TLE (YYDDD.DDDDDDDD).
+    pub fn epoch_text(&self) -> Result<&'a str, ParseError> {
+        field(self.line1, 1, 18..32, "epoch")
+    }
+
+    pub fn epoch(&self) -> Result<DateTime<Utc>, ParseError> {
+        let epoch_text = self.epoch_text()?;
+        get_epoch_from_tle(epoch_text).ok_or_else(|| ParseError::invalid(1, 18..32, "epoch", epoch_text))
+    }
+
+    pub fn first_derivative_mean_motion(&self) -> Result<f64, ParseError> {
+        parse_field(self.line1, 1, 33..43, "first_derivative_mean_motion")
+    }
+
+    pub fn second_derivative_mean_motion(&self) -> Result<f64, ParseError> {
+        parse_assumed_field(self.line1, 1, 44..52, "second_derivative_mean_motion")
+    }
+
+    pub fn drag_term(&self) -> Result<f64, ParseError> {
+        parse_assumed_field(self.line1, 1, 53..61, "drag_term")
+    }
+
+    pub fn ephemeris_type(&self) -> Result<u32, ParseError> {
+        parse_field(self.line1, 1, 62..63, "ephemeris_type")
+    }
+
+    pub fn element_number(&self) -> Result<u32, ParseError> {
+        parse_field(self.line1, 1, 64..68, "element_number")
+    }
+
+    pub fn inclination(&self) -> Result<f64, ParseError> {
+        parse_field(self.line2, 2, 8..16, "inclination")
+    }
+
+    pub fn right_ascension(&self) -> Result<f64, ParseError> {
+        parse_field(self.line2, 2, 17..25, "right_ascension")
+    }
+
+    pub fn eccentricity(&self) -> Result<f64, ParseError> {
+        parse_assumed_field(self.line2, 2, 26..33, "eccentricity")
+    }
+
+    pub fn argument_of_perigee(&self) -> Result<f64, ParseError> {
+        parse_field(self.line2, 2, 34..42, "argument_of_perigee")
+    }
+
+    pub fn mean_anomaly(&self) -> Result<f64, ParseError> {
+        parse_field(self.line2, 2, 43..51, "mean_anomaly")
+    }
+
+    pub fn mean_motion(&self) -> Result<f64, ParseError> {
+        parse_field(self.line2, 2, 52..63, "mean_motion")
+    }
+
+    pub fn revolution_number(&self) -> Result<u32, ParseError> {
+        parse_field(self.line2, 2, 63..68, "revolution_number")
+    }
+
+    // Checks both line checksums, failing on the first mismatch in
+    // ChecksumMode::Strict and collecting mismatches otherwise.
+    pub fn checksum_mismatches(&self, mode: ChecksumMode) -> Result<Vec<ChecksumMismatch>, ParseError> {
+        let mut checksum_mismatches: Vec<ChecksumMismatch> = Vec::new();
+        if mode == ChecksumMode::Off {
+            return Ok(checksum_mismatches);
+        }
+        for (line_number, line) in [(1, self.line1), (2, self.line2)] {
+            let expected = compute_checksum(line);
+            let found = checksum_digit(line);
+            if found == Some(expected) {
+                continue;
+            }
+            if mode == ChecksumMode::Strict {
+                return Err(ParseError {
+                    kind: ParseErrorKind::ChecksumMismatch,
+                    line: line_number,
+                    columns: (69, 69),
+                    field: "checksum",
+                    text: line.get(68..).unwrap_or("").to_string(),
+                });
+            }
+            checksum_mismatches.push(ChecksumMismatch { line: line_number, expected, found });
+        }
+        Ok(checksum_mismatches)
+    }
+
+    // Decodes every field into an owned TLE, as parse_tle does.
+    pub fn to_tle(&self) -> Result<TLE, ParseError> {
+        self.to_tle_with_checksum(ChecksumMode::default())
+    }
+
+    pub fn to_tle_with_checksum(&self, mode: ChecksumMode) -> Result<TLE, ParseError> {
+        let checksum_mismatches = self.checksum_mismatches(mode)?;
+        Ok(TLE {
+            name: self.name.to_string(),
+            satellite_number: self.satellite_number()?,
+            classification: self.classification()?,
+            international_designator: self.international_designator()?,
+            epoch: self.epoch()?,
+            first_derivative_mean_motion: self.first_derivative_mean_motion()?,
+            second_derivative_mean_motion: self.second_derivative_mean_motion()?,
+            drag_term: self.drag_term()?,
+            ephemeris_type: self.ephemeris_type()?,
+            element_number: self.element_number()?,
+            inclination: self.inclination()?,
+            right_ascension: self.right_ascension()?,
+            eccentricity: self.eccentricity()?,
+            argument_of_perigee: self.argument_of_perigee()?,
+            mean_anomaly: self.mean_anomaly()?,
+            mean_motion: self.mean_motion()?,
+            revolution_number: self.revolution_number()?,
+            checksum_mismatches,
+            last_updated_epoch: now().timestamp(),
+        })
+    }
+}
+
+
+// Splits a buffer of 2LE and/or 3LE records into borrowed records with
+// RecordSplitter, without copying any lines. Stray lines are yielded as
+// errors in the order they are found.
+pub fn tle_refs(input: &str) -> TleRefs<'_> {
+    TleRefs {
+        lines: input.lines(),
+        splitter: RecordSplitter::default(),
+        finished: false,
+    }
+}
+
+pub struct TleRefs<'a> {
+    lines: core::str::Lines<'a>,
+    splitter: RecordSplitter<&'a str>,
+    finished: bool,
+}
+
+impl<'a> Iterator for TleRefs<'a> {
+    type Item = Result<TleRef<'a>, StrayLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.splitter.next_grouped() {
+                return Some(match item {
+                    Ok((name, line1, line2)) => Ok(TleRef::from_lines(name.unwrap_or(""), line1, line2)),
+                    Err((line_number, text)) => Err(StrayLine { line_number, text: text.to_string() }),
+                });
+            }
+            if self.finished {
+                return None;
+            }
+            match self.lines.next() {
+                Some(line) => self.splitter.push(line.trim()),
+                None => {
+                    self.splitter.finish();
+                    self.finished = true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_tle, split_tle_records};
+
+    const TLES: &str = "0 ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791
+
+1 43890U 18111Q   20044.88470557  .00000320  00000-0  36258-4 0  9993
+2 43890  97.7009 312.6237 0003899   7.8254 352.3026 14.92889838 61757
+STRAY NAME
+GOES 14
+1 35491U 09033A   24169.87063115 -.00000044  00000+0  00000+0 0  9997
+2 35491   0.3405 104.0766 0003981 351.0753  16.3310  1.00272898 54803
+1 25338U 98030A   24169.93801846  .00000329  00000+0  15393-3 0  9999";
+
+    #[test]
+    fn test_tle_ref_matches_parse_tle() {
+        let split = split_tle_records(TLES);
+        let (records, stray_lines): (Vec<_>, Vec<_>) = tle_refs(TLES).partition(Result::is_ok);
+
+        assert_eq!(records.len(), split.records.len());
+        assert_eq!(stray_lines.into_iter().map(|line| line.unwrap_err()).collect::<Vec<StrayLine>>(), split.stray_lines);
+        for (tle_ref, record) in records.into_iter().zip(split.records.iter()) {
+            assert_eq!(tle_ref.unwrap().to_tle().unwrap(), parse_tle(record).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_tle_ref_fields() {
+        let tle = tle_refs(TLES).next().unwrap().unwrap();
+        assert_eq!(tle.name(), "ISS (ZARYA)");
+        assert_eq!(tle.satellite_number(), Ok(25544));
+        assert_eq!(tle.international_designator_text(), Ok("98067A"));
+        assert_eq!(tle.international_designator(), Ok(InternationalDesignator::from_cospar("1998-067A")));
+        assert_eq!(tle.epoch_text(), Ok("20045.18587073"));
+        assert_eq!(tle.drag_term(), Ok(0.25302e-4));
+        assert_eq!(tle.eccentricity(), Ok(0.0004885));
+        assert_eq!(tle.mean_motion(), Ok(15.49165514));
+
+        let short = TleRef::from_lines("", "1 25544U 98067A", "2 25544  51.6443");
+        assert_eq!(short.satellite_number(), Ok(25544));
+        assert_eq!(short.inclination(), Ok(51.6443));
+        assert_eq!(short.mean_motion().unwrap_err().kind, ParseErrorKind::MissingField);
+    }
+}
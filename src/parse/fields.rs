@@ -0,0 +1,234 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+
+// Describes a classification or designator that isn't in a valid form.
+#[derive(PartialEq, Debug, Clone)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub text: String,
+}
+
+impl Display for FieldError {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result {
+        write!(formatter, "invalid {}: {:?}", self.field, self.text)
+    }
+}
+
+impl core::error::Error for FieldError {}
+
+
+// The security classification in column 8 of line 1. Public catalogs only
+// contain unclassified elements, but the other letters are defined.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(try_from = "char", into = "char")]
+pub enum Classification {
+    #[default]
+    Unclassified,
+    Classified,
+    Secret,
+}
+
+impl Classification {
+    pub fn as_char(self) -> char {
+        match self {
+            Classification::Unclassified => 'U',
+            Classification::Classified => 'C',
+            Classification::Secret => 'S',
+        }
+    }
+}
+
+impl TryFrom<char> for Classification {
+    type Error = FieldError;
+
+    fn try_from(letter: char) -> Result<Self, Self::Error> {
+        match letter {
+            'U' => Ok(Classification::Unclassified),
+            'C' => Ok(Classification::Classified),
+            'S' => Ok(Classification::Secret),
+            _ => Err(FieldError { field: "classification", text: letter.to_string() }),
+        }
+    }
+}
+
+impl From<Classification> for char {
+    fn from(classification: Classification) -> Self {
+        classification.as_char()
+    }
+}
+
+impl FromStr for Classification {
+    type Err = FieldError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut letters = text.chars();
+        match (letters.next(), letters.next()) {
+            (Some(letter), None) => Classification::try_from(letter),
+            _ => Err(FieldError { field: "classification", text: text.to_string() }),
+        }
+    }
+}
+
+impl Display for Classification {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result {
+        write!(formatter, "{}", self.as_char())
+    }
+}
+
+
+// A COSPAR international designator: the launch year, the number of the
+// launch within that year and the 1 to 3 letter piece (1998-067A). TLEs
+// write it with a 2 digit year (98067A), which limits the launch year to
+// 1957-2056. The piece is kept inline so the type is Copy and parsing one
+// doesn't allocate.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct InternationalDesignator {
+    launch_year: u16,
+    launch_number: u16,
+    piece: [u8; 3],
+}
+
+impl InternationalDesignator {
+    // Returns None unless the year is in 1957-2056, the launch number is at
+    // most 999 and the piece is 1 to 3 uppercase letters.
+    pub fn new(launch_year: u16, launch_number: u16, piece: &str) -> Option<Self> {
+        if !(1957..=2056).contains(&launch_year) || launch_number > 999 {
+            return None;
+        }
+        if piece.is_empty() || piece.len() > 3 || !piece.bytes().all(|b| b.is_ascii_uppercase()) {
+            return None;
+        }
+        let mut letters = [b' '; 3];
+        letters[..piece.len()].copy_from_slice(piece.as_bytes());
+        Some(InternationalDesignator { launch_year, launch_number, piece: letters })
+    }
+
+    // Parses the TLE form, a 2 digit year, 3 digit launch number and piece (98067A).
+    pub fn from_tle(text: &str) -> Option<Self> {
+        let year = parse_digits(text.get(0..2)?)?;
+        let century = if year < 57 { 2000 } else { 1900 };
+        InternationalDesignator::new(century + year, parse_digits(text.get(2..5)?)?, text.get(5..)?)
+    }
+
+    // Parses the COSPAR form, a 4 digit year, dash, 3 digit launch number and piece (1998-067A).
+    pub fn from_cospar(text: &str) -> Option<Self> {
+        let (year, rest) = text.split_once('-')?;
+        if year.len() != 4 {
+            return None;
+        }
+        InternationalDesignator::new(parse_digits(year)?, parse_digits(rest.get(0..3)?)?, rest.get(3..)?)
+    }
+
+    pub fn launch_year(&self) -> u16 {
+        self.launch_year
+    }
+
+    pub fn launch_number(&self) -> u16 {
+        self.launch_number
+    }
+
+    pub fn piece(&self) -> &str {
+        // only ever holds ascii letters and padding
+        core::str::from_utf8(&self.piece).unwrap_or_default().trim_end()
+    }
+
+    // The designator as written in TLE columns 10-17 (98067A).
+    pub fn to_tle(&self) -> String {
+        format!("{:02}{:03}{}", self.launch_year % 100, self.launch_number, self.piece())
+    }
+}
+
+fn parse_digits(text: &str) -> Option<u16> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse::<u16>().ok()
+}
+
+// Accepts either the COSPAR or the TLE form.
+impl FromStr for InternationalDesignator {
+    type Err = FieldError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        InternationalDesignator::from_cospar(text)
+            .or_else(|| InternationalDesignator::from_tle(text))
+            .ok_or_else(|| FieldError { field: "international_designator", text: text.to_string() })
+    }
+}
+
+impl TryFrom<String> for InternationalDesignator {
+    type Error = FieldError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<InternationalDesignator> for String {
+    fn from(designator: InternationalDesignator) -> Self {
+        designator.to_string()
+    }
+}
+
+// Writes the COSPAR form (1998-067A).
+impl Display for InternationalDesignator {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result {
+        write!(formatter, "{}-{:03}{}", self.launch_year, self.launch_number, self.piece())
+    }
+}
+
+// Caches written before the designator was typed store a missing one as an
+// empty string rather than null.
+pub(super) fn deserialize_optional_designator<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<InternationalDesignator>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(text) if !text.is_empty() => text.parse().map(Some).map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classification() {
+        assert_eq!("U".parse(), Ok(Classification::Unclassified));
+        assert_eq!("S".parse(), Ok(Classification::Secret));
+        assert!("u".parse::<Classification>().is_err());
+        assert!("UC".parse::<Classification>().is_err());
+        assert_eq!(Classification::Classified.to_string(), "C");
+        assert_eq!(serde_json::to_string(&Classification::Unclassified).unwrap(), "\"U\"");
+    }
+
+    #[test]
+    fn test_international_designator() {
+        let iss = InternationalDesignator::from_tle("98067A").unwrap();
+        assert_eq!((iss.launch_year(), iss.launch_number(), iss.piece()), (1998, 67, "A"));
+        assert_eq!(iss.to_string(), "1998-067A");
+        assert_eq!(iss.to_tle(), "98067A");
+        assert_eq!("1998-067A".parse(), Ok(iss));
+
+        let debris = InternationalDesignator::from_tle("22002BQZ").unwrap();
+        assert_eq!((debris.launch_year(), debris.piece()), (2022, "BQZ"));
+        assert_eq!(InternationalDesignator::from_cospar("2022-002BQZ"), Some(debris));
+
+        for invalid in ["", "98067", "98067a", "98067ABCD", "9806AA", "98-067A", "1956-001A"] {
+            assert!(invalid.parse::<InternationalDesignator>().is_err(), "{}", invalid);
+        }
+
+        let json = serde_json::to_string(&iss).unwrap();
+        assert_eq!(json, "\"1998-067A\"");
+        assert_eq!(serde_json::from_str::<InternationalDesignator>(&json).unwrap(), iss);
+        assert_eq!(serde_json::from_str::<InternationalDesignator>("\"98067A\"").unwrap(), iss);
+    }
+}
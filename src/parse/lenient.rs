@@ -0,0 +1,391 @@
+use super::{compute_checksum, ChecksumMode, ParseError, TleRef, TLE};
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+
+
+// A change parse_tle_lenient made to a line before parsing it. `line` is
+// 1 or 2.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Correction {
+    // Tabs were replaced with spaces.
+    Tabs { line: usize },
+    // Whitespace after the last column was removed.
+    TrailingWhitespace { line: usize },
+    // The fields weren't in their columns, so they were split on
+    // whitespace and written back into the standard layout.
+    Realigned { line: usize },
+    // Leading zeros written as spaces, or left out, were restored.
+    LeadingZeros { line: usize, field: &'static str },
+    // A lowercase classification letter was uppercased.
+    LowercaseClassification,
+    // A line ending before column 68 was padded with spaces.
+    ShortLine { line: usize, length: usize },
+    // A field missing from the end of a line was set to its default.
+    DefaultedField { line: usize, field: &'static str },
+    // The line had no checksum, so one was computed and appended.
+    MissingChecksum { line: usize },
+}
+
+impl Display for Correction {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result {
+        match self {
+            Correction::Tabs { line } => write!(formatter, "line {}: replaced tabs", line),
+            Correction::TrailingWhitespace { line } => write!(formatter, "line {}: removed trailing whitespace", line),
+            Correction::Realigned { line } => write!(formatter, "line {}: realigned fields into their columns", line),
+            Correction::LeadingZeros { line, field } => write!(formatter, "line {}: restored leading zeros in {}", line, field),
+            Correction::LowercaseClassification => write!(formatter, "line 1: uppercased classification"),
+            Correction::ShortLine { line, length } => write!(formatter, "line {}: padded {} characters to 68", line, length),
+            Correction::DefaultedField { line, field } => write!(formatter, "line {}: defaulted missing {}", line, field),
+            Correction::MissingChecksum { line } => write!(formatter, "line {}: computed missing checksum", line),
+        }
+    }
+}
+
+// A TLE parsed by parse_tle_lenient and the corrections needed to parse it.
+#[derive(PartialEq, Debug)]
+pub struct LenientTle {
+    pub tle: TLE,
+    pub corrections: Vec<Correction>,
+}
+
+
+// Parses a 2 or 3 line element that may have been hand edited or passed
+// through tools that don't respect the fixed columns. Each line is
+// normalised before parsing: tabs and trailing whitespace are removed,
+// fields that have drifted out of their columns are realigned, missing
+// leading zeros are restored, a lowercase classification is uppercased and
+// short lines are padded, defaulting the optional fields at the end. Wrong
+// checksums are flagged as parse_tle does; missing ones are computed.
+// Anything that can't be repaired is reported as a ParseError.
+pub fn parse_tle_lenient(tle: &str) -> Result<LenientTle, ParseError> {
+    let mut lines = tle.lines().filter(|line| !line.trim().is_empty()).peekable();
+
+    let name = match lines.peek() {
+        Some(line) if is_line(line, '1') => "",
+        _ => lines.next().ok_or_else(|| ParseError::missing_line(0))?,
+    };
+    let line1 = lines.next().ok_or_else(|| ParseError::missing_line(1))?;
+    let line2 = lines.next().ok_or_else(|| ParseError::missing_line(2))?;
+
+    // the repairs work on byte columns, which only line up with ASCII
+    for (line, text) in [(1, line1), (2, line2)] {
+        if let Some(column) = text.find(|c: char| !c.is_ascii()) {
+            return Err(ParseError::invalid(line, column..column + 1, "line", text));
+        }
+    }
+
+    let mut corrections: Vec<Correction> = Vec::new();
+    let line1 = normalise_line(line1, 1, &mut corrections);
+    let line2 = normalise_line(line2, 2, &mut corrections);
+
+    let tle = TleRef::from_lines(name, &line1, &line2).to_tle_with_checksum(ChecksumMode::Warn)?;
+    Ok(LenientTle { tle, corrections })
+}
+
+fn is_line(text: &str, number: char) -> bool {
+    let mut chars = text.trim_start().chars();
+    chars.next() == Some(number) && chars.next().is_some_and(char::is_whitespace)
+}
+
+
+// Blank columns between the fields of each line, 0-based.
+const LINE1_SEPARATORS: [usize; 8] = [1, 8, 17, 32, 43, 52, 61, 63];
+const LINE2_SEPARATORS: [usize; 7] = [1, 7, 16, 25, 33, 42, 51];
+
+fn normalise_line(text: &str, line: usize, corrections: &mut Vec<Correction>) -> String {
+    let mut text = text.trim_start().to_string();
+    if text.contains('\t') {
+        text = text.replace('\t', " ");
+        corrections.push(Correction::Tabs { line });
+    }
+    if text.ends_with(char::is_whitespace) {
+        text.truncate(text.trim_end().len());
+        corrections.push(Correction::TrailingWhitespace { line });
+    }
+
+    let separators: &[usize] = if line == 1 { &LINE1_SEPARATORS } else { &LINE2_SEPARATORS };
+    let aligned = separators
+        .iter()
+        .all(|&column| text.as_bytes().get(column).is_none_or(|&b| b == b' '));
+    if !aligned {
+        let realigned = if line == 1 { realign_line1(&text, corrections) } else { realign_line2(&text, corrections) };
+        // lines that can't be realigned are parsed as they are, so the error points at the bad field
+        if let Some(realigned) = realigned {
+            text = realigned;
+            corrections.push(Correction::Realigned { line });
+        }
+    }
+
+    if line == 1 {
+        restore_zeros(&mut text, 2..7, line, "satellite_number", corrections);
+        restore_zeros(&mut text, 18..23, line, "epoch", corrections);
+        if let Some(classification) = text.get(7..8).filter(|letter| letter.bytes().all(|b| b.is_ascii_lowercase())) {
+            let uppercase = classification.to_ascii_uppercase();
+            text.replace_range(7..8, &uppercase);
+            corrections.push(Correction::LowercaseClassification);
+        }
+    } else {
+        restore_zeros(&mut text, 2..7, line, "satellite_number", corrections);
+        restore_zeros(&mut text, 26..33, line, "eccentricity", corrections);
+    }
+
+    // the last required field ends at column 61 on line 1 and 63 on line 2
+    let required = if line == 1 { 61 } else { 63 };
+    if text.len() >= required && text.len() < 68 {
+        corrections.push(Correction::ShortLine { line, length: text.len() });
+        text = format!("{:<68}", text);
+    }
+    if line == 1 {
+        default_blank(&mut text, 62..63, line, "ephemeris_type", corrections);
+        default_blank(&mut text, 64..68, line, "element_number", corrections);
+    } else {
+        default_blank(&mut text, 63..68, line, "revolution_number", corrections);
+    }
+    if text.len() == 68 {
+        text.push_str(&compute_checksum(&text).to_string());
+        corrections.push(Correction::MissingChecksum { line });
+    }
+    text
+}
+
+// Replaces spaces before the digits of a zero padded field with zeros.
+// Spaces add nothing to the checksum, so an existing checksum stays valid.
+fn restore_zeros(text: &mut String, columns: Range<usize>, line: usize, field: &'static str, corrections: &mut Vec<Correction>) {
+    let Some(value) = text.get(columns.clone()) else {
+        return;
+    };
+    let digits = value.trim_end();
+    if digits.is_empty() || !digits.contains(' ') {
+        return;
+    }
+    let zeroed = digits.replace(' ', "0");
+    text.replace_range(columns.start..columns.start + digits.len(), &zeroed);
+    corrections.push(Correction::LeadingZeros { line, field });
+}
+
+// Writes a right aligned 0 into an optional field left blank.
+fn default_blank(text: &mut String, columns: Range<usize>, line: usize, field: &'static str, corrections: &mut Vec<Correction>) {
+    if text.get(columns.clone()).is_some_and(|value| value.trim().is_empty()) {
+        let zero = format!("{:>1$}", 0, columns.len());
+        text.replace_range(columns, &zero);
+        corrections.push(Correction::DefaultedField { line, field });
+    }
+}
+
+
+// Rebuilds line 1 from its whitespace separated fields. The designator,
+// ephemeris type and element number may be absent.
+fn realign_line1(text: &str, corrections: &mut Vec<Correction>) -> Option<String> {
+    let mut tokens: VecDeque<&str> = text.split_whitespace().collect();
+    if tokens.pop_front()? != "1" {
+        return None;
+    }
+
+    let token = tokens.pop_front()?;
+    let (satellite_number, classification) = match token.char_indices().last() {
+        Some((index, letter)) if index > 0 && letter.is_ascii_alphabetic() => token.split_at(index),
+        _ if tokens.front()?.len() == 1 && tokens.front()?.bytes().all(|b| b.is_ascii_alphabetic()) => (token, tokens.pop_front()?),
+        _ => {
+            corrections.push(Correction::DefaultedField { line: 1, field: "classification" });
+            (token, "U")
+        }
+    };
+    let satellite_number = zero_pad(satellite_number, 5, 1, "satellite_number", corrections);
+    let designator = match tokens.front() {
+        Some(token) if !token.contains('.') => tokens.pop_front()?,
+        _ => "",
+    };
+    let epoch = zero_pad_epoch(tokens.pop_front()?, corrections)?;
+    let first_derivative = tokens.pop_front()?;
+    let second_derivative = tokens.pop_front()?;
+    let drag_term = tokens.pop_front()?;
+    let (ephemeris_type, last) = match (tokens.pop_front(), tokens.pop_front()) {
+        (Some(ephemeris_type), Some(last)) => (ephemeris_type, last),
+        (Some(token), None) if token.len() == 1 => (token, ""),
+        (Some(token), None) => (" ", token),
+        _ => (" ", ""),
+    };
+    if !tokens.is_empty() {
+        return None;
+    }
+
+    let body = format!(
+        "1 {}{} {:<8} {} {:>10} {:>8} {:>8} {:1} ",
+        satellite_number, classification, designator, epoch, first_derivative, second_derivative, drag_term, ephemeris_type,
+    );
+    if body.len() != 64 {
+        return None;
+    }
+    with_trailing_field(body, last, 4)
+}
+
+// Rebuilds line 2 from its whitespace separated fields. The mean motion,
+// revolution number and checksum are often run together; the mean motion
+// has 8 decimal places, so anything after those belongs to the revolution
+// number.
+fn realign_line2(text: &str, corrections: &mut Vec<Correction>) -> Option<String> {
+    let mut tokens: VecDeque<&str> = text.split_whitespace().collect();
+    if tokens.pop_front()? != "2" {
+        return None;
+    }
+    let satellite_number = zero_pad(tokens.pop_front()?, 5, 2, "satellite_number", corrections);
+    let inclination = tokens.pop_front()?;
+    let right_ascension = tokens.pop_front()?;
+    let eccentricity = tokens.pop_front()?;
+    let eccentricity = eccentricity.strip_prefix("0.").or_else(|| eccentricity.strip_prefix('.')).unwrap_or(eccentricity);
+    let argument_of_perigee = tokens.pop_front()?;
+    let mean_anomaly = tokens.pop_front()?;
+
+    let motion = tokens.pop_front()?;
+    let decimal_point = motion.find('.')?;
+    let (mean_motion, last) = match tokens.pop_front() {
+        Some(last) => (motion, last),
+        None => motion.split_at((decimal_point + 9).min(motion.len())),
+    };
+    if !tokens.is_empty() {
+        return None;
+    }
+    let eccentricity = zero_pad(eccentricity, 7, 2, "eccentricity", corrections);
+
+    let body = format!(
+        "2 {} {:>8} {:>8} {} {:>8} {:>8} {:>11}",
+        satellite_number, inclination, right_ascension, eccentricity, argument_of_perigee, mean_anomaly, mean_motion,
+    );
+    if body.len() != 63 {
+        return None;
+    }
+    with_trailing_field(body, last, 5)
+}
+
+fn zero_pad(value: &str, width: usize, line: usize, field: &'static str, corrections: &mut Vec<Correction>) -> String {
+    if value.len() < width {
+        corrections.push(Correction::LeadingZeros { line, field });
+    }
+    format!("{:0>1$}", value, width)
+}
+
+// Pads the day of year and fraction of an epoch written without its zeros
+// (20045.5 or 2045.18587073) to YYDDD.DDDDDDDD.
+fn zero_pad_epoch(epoch: &str, corrections: &mut Vec<Correction>) -> Option<String> {
+    let (whole, fraction) = epoch.split_once('.')?;
+    if whole.len() > 5 || fraction.len() > 8 || whole.len() < 3 {
+        return None;
+    }
+    let (year, day) = whole.split_at(2);
+    Some(format!("{}{}.{:0<8}", year, zero_pad(day, 3, 1, "epoch", corrections), fraction))
+}
+
+// Appends the last field of a line, which may have its checksum digit run
+// on to it. The final digit is taken as the checksum when it is one, or
+// when the field would otherwise be too wide for its columns.
+fn with_trailing_field(body: String, last: &str, width: usize) -> Option<String> {
+    if last.len() > width + 1 {
+        return None;
+    }
+    if let Some((split, checksum)) = last.char_indices().last().filter(|(split, _)| *split > 0) {
+        let line = format!("{}{:>2$}", body, &last[..split], width);
+        if last.len() == width + 1 || checksum.to_digit(10) == Some(compute_checksum(&line) as u32) {
+            return Some(format!("{}{}", line, checksum));
+        }
+    }
+    Some(format!("{}{:>2$}", body, last, width).trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_tle;
+
+    const ISS: &str = "ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791";
+
+    #[test]
+    fn test_clean_tle_needs_no_corrections() {
+        let lenient = parse_tle_lenient(ISS).unwrap();
+        assert_eq!(lenient.tle, parse_tle(ISS).unwrap());
+        assert_eq!(lenient.corrections, vec![]);
+    }
+
+    #[test]
+    fn test_tabs_and_trailing_whitespace() {
+        let tabbed = "ISS (ZARYA)
+1\t25544U\t98067A\t20045.18587073\t.00000950\t00000-0\t25302-4\t0\t9990
+2\t25544\t51.6443\t242.0161\t0004885\t264.6060\t207.3845\t15.49165514212791\t";
+        let lenient = parse_tle_lenient(tabbed).unwrap();
+        assert_eq!(lenient.tle, parse_tle(ISS).unwrap());
+        assert_eq!(lenient.corrections, vec![
+            Correction::Tabs { line: 1 },
+            Correction::Realigned { line: 1 },
+            Correction::Tabs { line: 2 },
+            Correction::TrailingWhitespace { line: 2 },
+            Correction::Realigned { line: 2 },
+        ]);
+    }
+
+    #[test]
+    fn test_leading_zeros_and_lowercase() {
+        let defective = "ISS (ZARYA)
+1 25544u 98067A   20 45.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161    4885 264.6060 207.3845 15.49165514212791";
+        let lenient = parse_tle_lenient(defective).unwrap();
+        assert_eq!(lenient.tle, parse_tle(ISS).unwrap());
+        assert_eq!(lenient.corrections, vec![
+            Correction::LeadingZeros { line: 1, field: "epoch" },
+            Correction::LowercaseClassification,
+            Correction::LeadingZeros { line: 2, field: "eccentricity" },
+        ]);
+
+        let unpadded = "1 25544U 98067A 20045.18587073 .00000950 00000-0 25302-4 0 9990
+2 25544 51.6443 242.0161 4885 264.6060 207.3845 15.49165514 21279";
+        let lenient = parse_tle_lenient(unpadded).unwrap();
+        assert_eq!(lenient.tle.eccentricity, 0.0004885);
+        assert_eq!(lenient.tle.revolution_number, 21279);
+        assert!(lenient.tle.checksum_mismatches.is_empty());
+        assert!(lenient.corrections.contains(&Correction::LeadingZeros { line: 2, field: "eccentricity" }));
+    }
+
+    #[test]
+    fn test_short_lines() {
+        let short = "1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514";
+        let lenient = parse_tle_lenient(short).unwrap();
+        assert_eq!(lenient.tle.ephemeris_type, 0);
+        assert_eq!(lenient.tle.element_number, 0);
+        assert_eq!(lenient.tle.revolution_number, 0);
+        assert_eq!(lenient.tle.mean_motion, 15.49165514);
+        assert_eq!(lenient.corrections, vec![
+            Correction::ShortLine { line: 1, length: 61 },
+            Correction::DefaultedField { line: 1, field: "ephemeris_type" },
+            Correction::DefaultedField { line: 1, field: "element_number" },
+            Correction::MissingChecksum { line: 1 },
+            Correction::ShortLine { line: 2, length: 63 },
+            Correction::DefaultedField { line: 2, field: "revolution_number" },
+            Correction::MissingChecksum { line: 2 },
+        ]);
+
+        // too short to recover the mean motion
+        let err = parse_tle_lenient("1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845").unwrap_err();
+        assert_eq!(err.field, "mean_motion");
+    }
+
+    #[test]
+    fn test_non_ascii() {
+        let accented = "ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.4916514é12791";
+        let err = parse_tle_lenient(accented).unwrap_err();
+        assert_eq!(err.kind, crate::parse::ParseErrorKind::InvalidValue);
+        assert_eq!((err.line, err.columns, err.field), (2, (63, 63), "line"));
+
+        let err = parse_tle_lenient("1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0 \u{a0}9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap_err();
+        assert_eq!((err.line, err.field), (1, "line"));
+    }
+}
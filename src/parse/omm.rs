@@ -0,0 +1,666 @@
+use super::{now, InternationalDesignator, TLE};
+use chrono::{DateTime, NaiveDateTime, Utc};
+#[cfg(feature = "std")]
+use quick_xml::events::Event;
+#[cfg(feature = "std")]
+use quick_xml::Reader;
+use serde_json::Value;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+
+// Describes why a CCSDS Orbit Mean-Elements Message could not be read.
+#[derive(PartialEq, Debug, Clone)]
+pub enum OmmError {
+    // The document itself is malformed, e.g. invalid XML.
+    Syntax(String),
+    // A keyword required to build a TLE is absent.
+    MissingKeyword(&'static str),
+    // A keyword is present but its value could not be parsed.
+    InvalidValue { keyword: String, text: String },
+}
+
+impl Display for OmmError {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result {
+        match self {
+            OmmError::Syntax(message) => write!(formatter, "malformed OMM: {}", message),
+            OmmError::MissingKeyword(keyword) => write!(formatter, "missing OMM keyword {}", keyword),
+            OmmError::InvalidValue { keyword, text } => {
+                write!(formatter, "could not parse OMM keyword {} {:?}", keyword, text)
+            }
+        }
+    }
+}
+
+impl core::error::Error for OmmError {}
+
+
+// Parses an OMM XML document, either a single `<omm>` or an `<ndm>` holding
+// several, into one TLE per `<segment>`. Each segment is converted on its own
+// so a bad segment can be skipped; malformed XML ends the list with an error.
+#[cfg(feature = "std")]
+pub fn parse_omm_xml(xml: &str) -> Vec<Result<TLE, OmmError>> {
+    let mut tles: Vec<Result<TLE, OmmError>> = Vec::new();
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut fields: Option<BTreeMap<String, String>> = None;
+    let mut element: Option<String> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start)) => {
+                let name = String::from_utf8_lossy(start.local_name().as_ref()).to_string();
+                if name == "segment" {
+                    fields = Some(BTreeMap::new());
+                    element = None;
+                } else if name == "USER_DEFINED" {
+                    // User defined parameters are keyed by their `parameter` attribute.
+                    element = match start.try_get_attribute("parameter") {
+                        Ok(Some(parameter)) => Some(format!(
+                            "USER_DEFINED_{}",
+                            String::from_utf8_lossy(&parameter.value)
+                        )),
+                        _ => None,
+                    };
+                } else {
+                    element = Some(name);
+                }
+            }
+            Ok(Event::Text(text)) => {
+                if let (Some(fields), Some(name)) = (fields.as_mut(), element.as_ref()) {
+                    match text.unescape() {
+                        Ok(value) => {
+                            fields.insert(name.clone(), value.trim().to_string());
+                        }
+                        Err(e) => {
+                            tles.push(Err(OmmError::Syntax(e.to_string())));
+                            return tles;
+                        }
+                    }
+                }
+            }
+            Ok(Event::End(end)) => {
+                element = None;
+                if end.local_name().as_ref() == b"segment" {
+                    if let Some(fields) = fields.take() {
+                        tles.push(tle_from_fields(&fields));
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => {
+                tles.push(Err(OmmError::Syntax(format!(
+                    "{} at position {}",
+                    e,
+                    reader.error_position()
+                ))));
+                break;
+            }
+        }
+    }
+
+    tles
+}
+
+
+// Parses Celestrak's OMM JSON output (FORMAT=json), an array of objects
+// keyed by OMM keyword, into one TLE per object.
+pub fn parse_omm_json(json: &str) -> Vec<Result<TLE, OmmError>> {
+    let objects = match serde_json::from_str::<Value>(json) {
+        Ok(Value::Array(objects)) => objects,
+        Ok(object @ Value::Object(_)) => vec![object],
+        Ok(_) => return vec![Err(OmmError::Syntax("expected an array of objects".to_string()))],
+        Err(e) => return vec![Err(OmmError::Syntax(e.to_string()))],
+    };
+
+    objects
+        .into_iter()
+        .map(|object| match object {
+            Value::Object(object) => {
+                let fields: BTreeMap<String, String> = object
+                    .into_iter()
+                    .filter_map(|(keyword, value)| match value {
+                        Value::String(text) => Some((keyword, text)),
+                        Value::Number(number) => Some((keyword, number.to_string())),
+                        _ => None,
+                    })
+                    .collect();
+                tle_from_fields(&fields)
+            }
+            _ => Err(OmmError::Syntax("expected an object".to_string())),
+        })
+        .collect()
+}
+
+
+// Parses Celestrak's OMM CSV output (FORMAT=csv), a header row of OMM
+// keywords followed by one row per object, into one TLE per row.
+#[cfg(feature = "std")]
+pub fn parse_omm_csv(csv: &str) -> Vec<Result<TLE, OmmError>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => return vec![Err(OmmError::Syntax(e.to_string()))],
+    };
+
+    reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|e| OmmError::Syntax(e.to_string()))?;
+            let fields: BTreeMap<String, String> = headers
+                .iter()
+                .zip(record.iter())
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(keyword, value)| (keyword.to_string(), value.to_string()))
+                .collect();
+            tle_from_fields(&fields)
+        })
+        .collect()
+}
+
+
+// The header block of an OMM, kept so KVN files can be written back out as read.
+#[derive(PartialEq, Debug, Clone)]
+pub struct OmmHeader {
+    pub version: String,
+    pub creation_date: String,
+    pub originator: String,
+    pub message_id: Option<String>,
+    pub comments: Vec<String>,
+}
+
+// The metadata block of an OMM. OBJECT_NAME and OBJECT_ID are held on the TLE.
+#[derive(PartialEq, Debug, Clone)]
+pub struct OmmMetadata {
+    pub center_name: String,
+    pub ref_frame: String,
+    pub ref_frame_epoch: Option<String>,
+    pub time_system: String,
+    pub mean_element_theory: String,
+    pub comments: Vec<String>,
+}
+
+// A complete Orbit Mean-Elements Message: the elements as a TLE along with
+// the header, metadata, data block comments, the optional spacecraft
+// parameters and covariance matrix (keyed by keyword, values without units)
+// and user defined parameters (keyed without their USER_DEFINED_ prefix).
+#[derive(PartialEq, Debug)]
+pub struct Omm {
+    pub header: OmmHeader,
+    pub metadata: OmmMetadata,
+    pub data_comments: Vec<String>,
+    pub tle: TLE,
+    pub spacecraft_parameters: BTreeMap<String, String>,
+    pub covariance: BTreeMap<String, String>,
+    pub user_defined: BTreeMap<String, String>,
+}
+
+impl Omm {
+    // Wraps a TLE in an OMM with the standard SGP4 metadata and a header
+    // created now, ready to be written out. Without std there is no clock,
+    // so use created_at with the creation date instead.
+    #[cfg(feature = "std")]
+    pub fn new(tle: TLE) -> Self {
+        Omm::created_at(tle, Utc::now())
+    }
+
+    // Wraps a TLE in an OMM as new does, with the given creation date.
+    pub fn created_at(tle: TLE, creation_date: DateTime<Utc>) -> Self {
+        let mean_element_theory = if tle.ephemeris_type == 4 { "SGP4-XP" } else { "SGP4" };
+        Omm {
+            header: OmmHeader {
+                version: "2.0".to_string(),
+                creation_date: creation_date.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                originator: "UNKNOWN".to_string(),
+                message_id: None,
+                comments: Vec::new(),
+            },
+            metadata: OmmMetadata {
+                center_name: "EARTH".to_string(),
+                ref_frame: "TEME".to_string(),
+                ref_frame_epoch: None,
+                time_system: "UTC".to_string(),
+                mean_element_theory: mean_element_theory.to_string(),
+                comments: Vec::new(),
+            },
+            data_comments: Vec::new(),
+            tle,
+            spacecraft_parameters: BTreeMap::new(),
+            covariance: BTreeMap::new(),
+            user_defined: BTreeMap::new(),
+        }
+    }
+}
+
+const KVN_HEADER_KEYWORDS: [&str; 4] = ["CCSDS_OMM_VERS", "CREATION_DATE", "ORIGINATOR", "MESSAGE_ID"];
+const KVN_METADATA_KEYWORDS: [&str; 7] = [
+    "OBJECT_NAME",
+    "OBJECT_ID",
+    "CENTER_NAME",
+    "REF_FRAME",
+    "REF_FRAME_EPOCH",
+    "TIME_SYSTEM",
+    "MEAN_ELEMENT_THEORY",
+];
+// The spacecraft parameters and covariance matrix keywords with their
+// units, in the order the standard lists them.
+pub(crate) const KVN_SPACECRAFT_KEYWORDS: [(&str, &str); 5] = [
+    ("MASS", "kg"),
+    ("SOLAR_RAD_AREA", "m**2"),
+    ("SOLAR_RAD_COEFF", ""),
+    ("DRAG_AREA", "m**2"),
+    ("DRAG_COEFF", ""),
+];
+pub(crate) const KVN_COVARIANCE_KEYWORDS: [(&str, &str); 22] = [
+    ("COV_REF_FRAME", ""),
+    ("CX_X", "km**2"),
+    ("CY_X", "km**2"),
+    ("CY_Y", "km**2"),
+    ("CZ_X", "km**2"),
+    ("CZ_Y", "km**2"),
+    ("CZ_Z", "km**2"),
+    ("CX_DOT_X", "km**2/s"),
+    ("CX_DOT_Y", "km**2/s"),
+    ("CX_DOT_Z", "km**2/s"),
+    ("CX_DOT_X_DOT", "km**2/s**2"),
+    ("CY_DOT_X", "km**2/s"),
+    ("CY_DOT_Y", "km**2/s"),
+    ("CY_DOT_Z", "km**2/s"),
+    ("CY_DOT_X_DOT", "km**2/s**2"),
+    ("CY_DOT_Y_DOT", "km**2/s**2"),
+    ("CZ_DOT_X", "km**2/s"),
+    ("CZ_DOT_Y", "km**2/s"),
+    ("CZ_DOT_Z", "km**2/s"),
+    ("CZ_DOT_X_DOT", "km**2/s**2"),
+    ("CZ_DOT_Y_DOT", "km**2/s**2"),
+    ("CZ_DOT_Z_DOT", "km**2/s**2"),
+];
+
+
+// Parses an OMM in key-value notation. Units in square brackets after values
+// are ignored, as are the META_START/META_STOP style block delimiters.
+// Comments are assigned to the header, metadata or data block of the
+// keyword that follows them.
+pub fn parse_omm_kvn(kvn: &str) -> Result<Omm, OmmError> {
+    let mut fields: BTreeMap<String, String> = BTreeMap::new();
+    let mut spacecraft_parameters: BTreeMap<String, String> = BTreeMap::new();
+    let mut covariance: BTreeMap<String, String> = BTreeMap::new();
+    let mut user_defined: BTreeMap<String, String> = BTreeMap::new();
+    let mut header_comments: Vec<String> = Vec::new();
+    let mut metadata_comments: Vec<String> = Vec::new();
+    let mut data_comments: Vec<String> = Vec::new();
+    let mut pending_comments: Vec<String> = Vec::new();
+
+    for (index, line) in kvn.lines().enumerate() {
+        let line = line.trim();
+        let is_delimiter = !line.contains(['=', ' ', '\t']) && (line.ends_with("_START") || line.ends_with("_STOP"));
+        if line.is_empty() || is_delimiter {
+            continue;
+        }
+        if line == "COMMENT" || line.starts_with("COMMENT ") || line.starts_with("COMMENT\t") {
+            pending_comments.push(line["COMMENT".len()..].trim().to_string());
+            continue;
+        }
+
+        let (keyword, value) = line.split_once('=').ok_or_else(|| {
+            OmmError::Syntax(format!("expected KEYWORD = value on line {}", index + 1))
+        })?;
+        let keyword = keyword.trim();
+        let value = match value.find('[') {
+            Some(units) => value[..units].trim(),
+            None => value.trim(),
+        };
+
+        let comments = if KVN_HEADER_KEYWORDS.contains(&keyword) {
+            &mut header_comments
+        } else if KVN_METADATA_KEYWORDS.contains(&keyword) {
+            &mut metadata_comments
+        } else {
+            &mut data_comments
+        };
+        comments.append(&mut pending_comments);
+
+        let parameters = if KVN_SPACECRAFT_KEYWORDS.iter().any(|(known, _)| *known == keyword) {
+            &mut spacecraft_parameters
+        } else if KVN_COVARIANCE_KEYWORDS.iter().any(|(known, _)| *known == keyword) {
+            &mut covariance
+        } else {
+            &mut fields
+        };
+        match keyword.strip_prefix("USER_DEFINED_") {
+            Some(parameter) => user_defined.insert(parameter.to_string(), value.to_string()),
+            None => parameters.insert(keyword.to_string(), value.to_string()),
+        };
+    }
+    data_comments.append(&mut pending_comments);
+
+    let text = |keyword: &str, default: &str| fields.get(keyword).cloned().unwrap_or(default.to_string());
+    Ok(Omm {
+        header: OmmHeader {
+            version: required(&fields, "CCSDS_OMM_VERS")?.to_string(),
+            creation_date: text("CREATION_DATE", ""),
+            originator: text("ORIGINATOR", ""),
+            message_id: fields.get("MESSAGE_ID").cloned(),
+            comments: header_comments,
+        },
+        metadata: OmmMetadata {
+            center_name: text("CENTER_NAME", "EARTH"),
+            ref_frame: text("REF_FRAME", "TEME"),
+            ref_frame_epoch: fields.get("REF_FRAME_EPOCH").cloned(),
+            time_system: text("TIME_SYSTEM", "UTC"),
+            mean_element_theory: text("MEAN_ELEMENT_THEORY", "SGP4"),
+            comments: metadata_comments,
+        },
+        data_comments,
+        tle: tle_from_fields(&fields)?,
+        spacecraft_parameters,
+        covariance,
+        user_defined,
+    })
+}
+
+
+// Builds a TLE from OMM keyword/value pairs. The keywords are shared by
+// every OMM encoding, so this is used regardless of the input format.
+fn tle_from_fields(fields: &BTreeMap<String, String>) -> Result<TLE, OmmError> {
+    let epoch = parse_omm_epoch(required(fields, "EPOCH")?)
+        .ok_or_else(|| invalid(fields, "EPOCH"))?;
+
+    Ok(TLE {
+        name: fields.get("OBJECT_NAME").cloned().unwrap_or_default(),
+        satellite_number: parse_required(fields, "NORAD_CAT_ID")?,
+        classification: parse_optional(fields, "CLASSIFICATION_TYPE")?.unwrap_or_default(),
+        international_designator: designator_from_object_id(fields)?,
+        epoch,
+        first_derivative_mean_motion: parse_optional(fields, "MEAN_MOTION_DOT")?.unwrap_or(0.0),
+        second_derivative_mean_motion: parse_optional(fields, "MEAN_MOTION_DDOT")?.unwrap_or(0.0),
+        drag_term: parse_optional(fields, "BSTAR")?.unwrap_or(0.0),
+        ephemeris_type: parse_optional(fields, "EPHEMERIS_TYPE")?.unwrap_or(0),
+        element_number: parse_optional(fields, "ELEMENT_SET_NO")?.unwrap_or(0),
+        inclination: parse_required(fields, "INCLINATION")?,
+        right_ascension: parse_required(fields, "RA_OF_ASC_NODE")?,
+        eccentricity: parse_required(fields, "ECCENTRICITY")?,
+        argument_of_perigee: parse_required(fields, "ARG_OF_PERICENTER")?,
+        mean_anomaly: parse_required(fields, "MEAN_ANOMALY")?,
+        mean_motion: parse_required(fields, "MEAN_MOTION")?,
+        revolution_number: parse_optional(fields, "REV_AT_EPOCH")?.unwrap_or(0),
+        checksum_mismatches: Vec::new(),
+        last_updated_epoch: now().timestamp(),
+    })
+}
+
+fn required<'a>(fields: &'a BTreeMap<String, String>, keyword: &'static str) -> Result<&'a str, OmmError> {
+    fields
+        .get(keyword)
+        .map(String::as_str)
+        .ok_or(OmmError::MissingKeyword(keyword))
+}
+
+fn invalid(fields: &BTreeMap<String, String>, keyword: &str) -> OmmError {
+    OmmError::InvalidValue {
+        keyword: keyword.to_string(),
+        text: fields.get(keyword).cloned().unwrap_or_default(),
+    }
+}
+
+fn parse_required<T: FromStr>(fields: &BTreeMap<String, String>, keyword: &'static str) -> Result<T, OmmError> {
+    required(fields, keyword)?
+        .parse::<T>()
+        .map_err(|_| invalid(fields, keyword))
+}
+
+fn parse_optional<T: FromStr>(fields: &BTreeMap<String, String>, keyword: &'static str) -> Result<Option<T>, OmmError> {
+    match fields.get(keyword) {
+        Some(text) => text.parse::<T>().map(Some).map_err(|_| invalid(fields, keyword)),
+        None => Ok(None),
+    }
+}
+
+// Parses an OMM epoch in either calendar (2020-02-14T04:27:39.231) or
+// day of year (2020-045T04:27:39.231) form, optionally suffixed with Z.
+fn parse_omm_epoch(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%jT%H:%M:%S%.f"))
+        .ok()
+        .map(|date_time| date_time.and_utc())
+}
+
+// Reads OBJECT_ID as a COSPAR id (1998-067A). OMM writers use UNKNOWN
+// when there is no designator.
+fn designator_from_object_id(fields: &BTreeMap<String, String>) -> Result<Option<InternationalDesignator>, OmmError> {
+    match fields.get("OBJECT_ID").map(String::as_str) {
+        None | Some("") | Some("UNKNOWN") => Ok(None),
+        Some(object_id) => object_id.parse().map(Some).map_err(|_| invalid(fields, "OBJECT_ID")),
+    }
+}
+
+// Writes a designator as an OBJECT_ID, using UNKNOWN when there is none.
+pub(crate) fn object_id_from_designator(designator: Option<InternationalDesignator>) -> String {
+    designator
+        .map(|designator| designator.to_string())
+        .unwrap_or_else(|| "UNKNOWN".to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::parse::parse_tle;
+
+    #[cfg(feature = "std")]
+    const NDM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ndm xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:noNamespaceSchemaLocation="https://sanaregistry.org/r/ndmxml_unqualified/ndmxml-2.0.0-master-2.0.xsd">
+<omm id="CCSDS_OMM_VERS" version="2.0">
+<header><CREATION_DATE/><ORIGINATOR/></header>
+<body><segment><metadata><OBJECT_NAME>ISS (ZARYA)</OBJECT_NAME><OBJECT_ID>1998-067A</OBJECT_ID><CENTER_NAME>EARTH</CENTER_NAME><REF_FRAME>TEME</REF_FRAME><TIME_SYSTEM>UTC</TIME_SYSTEM><MEAN_ELEMENT_THEORY>SGP4</MEAN_ELEMENT_THEORY></metadata><data><meanElements><EPOCH>2020-02-14T04:27:39.231072</EPOCH><MEAN_MOTION>15.49165514</MEAN_MOTION><ECCENTRICITY>.0004885</ECCENTRICITY><INCLINATION>51.6443</INCLINATION><RA_OF_ASC_NODE>242.0161</RA_OF_ASC_NODE><ARG_OF_PERICENTER>264.606</ARG_OF_PERICENTER><MEAN_ANOMALY>207.3845</MEAN_ANOMALY></meanElements><tleParameters><EPHEMERIS_TYPE>0</EPHEMERIS_TYPE><CLASSIFICATION_TYPE>U</CLASSIFICATION_TYPE><NORAD_CAT_ID>25544</NORAD_CAT_ID><ELEMENT_SET_NO>999</ELEMENT_SET_NO><REV_AT_EPOCH>21279</REV_AT_EPOCH><BSTAR>.25302E-4</BSTAR><MEAN_MOTION_DOT>.0000095</MEAN_MOTION_DOT><MEAN_MOTION_DDOT>0</MEAN_MOTION_DDOT></tleParameters></data></segment></body></omm>
+<omm id="CCSDS_OMM_VERS" version="2.0">
+<header><CREATION_DATE/><ORIGINATOR/></header>
+<body><segment><metadata><OBJECT_NAME>GOES 14</OBJECT_NAME><OBJECT_ID>2009-033A</OBJECT_ID><CENTER_NAME>EARTH</CENTER_NAME><REF_FRAME>TEME</REF_FRAME><TIME_SYSTEM>UTC</TIME_SYSTEM><MEAN_ELEMENT_THEORY>SGP4</MEAN_ELEMENT_THEORY></metadata><data><meanElements><EPOCH>2024-06-17T20:53:42.531360</EPOCH><MEAN_MOTION>1.00272898</MEAN_MOTION><ECCENTRICITY>.0003981</ECCENTRICITY><INCLINATION>.3405</INCLINATION><RA_OF_ASC_NODE>104.0766</RA_OF_ASC_NODE><ARG_OF_PERICENTER>351.0753</ARG_OF_PERICENTER><MEAN_ANOMALY>16.331</MEAN_ANOMALY></meanElements><tleParameters><EPHEMERIS_TYPE>0</EPHEMERIS_TYPE><CLASSIFICATION_TYPE>U</CLASSIFICATION_TYPE><NORAD_CAT_ID>35491</NORAD_CAT_ID><ELEMENT_SET_NO>999</ELEMENT_SET_NO><REV_AT_EPOCH>5480</REV_AT_EPOCH><BSTAR>0</BSTAR><MEAN_MOTION_DOT>-4.4E-7</MEAN_MOTION_DOT><MEAN_MOTION_DDOT>0</MEAN_MOTION_DDOT></tleParameters><userDefinedParameters><USER_DEFINED parameter="OPERATOR">NOAA</USER_DEFINED></userDefinedParameters></data></segment></body></omm>
+</ndm>"#;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_omm_xml() {
+        let tles: Vec<TLE> = parse_omm_xml(NDM).into_iter().map(Result::unwrap).collect();
+        assert_eq!(tles.len(), 2);
+
+        let tle = &tles[0];
+        let from_tle = parse_tle("ISS (ZARYA)
+        1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+        2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap();
+        assert_eq!(tle.name, from_tle.name);
+        assert_eq!(tle.satellite_number, from_tle.satellite_number);
+        assert_eq!(tle.international_designator, from_tle.international_designator);
+        assert_eq!(tle.epoch, from_tle.epoch);
+        assert_eq!(tle.epoch.to_rfc3339(), "2020-02-14T04:27:39.231072+00:00");
+        assert_eq!(tle.first_derivative_mean_motion, from_tle.first_derivative_mean_motion);
+        assert_eq!(tle.second_derivative_mean_motion, from_tle.second_derivative_mean_motion);
+        assert_eq!(tle.drag_term, from_tle.drag_term);
+        assert_eq!(tle.element_number, from_tle.element_number);
+        assert_eq!(tle.inclination, from_tle.inclination);
+        assert_eq!(tle.right_ascension, from_tle.right_ascension);
+        assert_eq!(tle.eccentricity, from_tle.eccentricity);
+        assert_eq!(tle.argument_of_perigee, from_tle.argument_of_perigee);
+        assert_eq!(tle.mean_anomaly, from_tle.mean_anomaly);
+        assert_eq!(tle.mean_motion, from_tle.mean_motion);
+        assert_eq!(tle.revolution_number, from_tle.revolution_number);
+
+        assert_eq!(tles[1].name, "GOES 14");
+        assert_eq!(tles[1].international_designator.unwrap().to_tle(), "09033A");
+        assert_eq!(tles[1].first_derivative_mean_motion, -0.00000044);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_omm_xml_errors() {
+        let missing = NDM.replace("<MEAN_MOTION>15.49165514</MEAN_MOTION>", "");
+        let tles = parse_omm_xml(&missing);
+        assert_eq!(tles[0], Err(OmmError::MissingKeyword("MEAN_MOTION")));
+        assert!(tles[1].is_ok());
+
+        let invalid = NDM.replace("<NORAD_CAT_ID>25544</NORAD_CAT_ID>", "<NORAD_CAT_ID>ISS</NORAD_CAT_ID>");
+        assert_eq!(
+            parse_omm_xml(&invalid)[0],
+            Err(OmmError::InvalidValue { keyword: "NORAD_CAT_ID".to_string(), text: "ISS".to_string() })
+        );
+
+        let malformed = parse_omm_xml("<ndm><omm><body><segment></data></segment></body></omm></ndm>");
+        assert!(matches!(malformed.last(), Some(Err(OmmError::Syntax(_)))));
+    }
+
+    const JSON: &str = r#"[{
+        "OBJECT_NAME": "ISS (ZARYA)",
+        "OBJECT_ID": "1998-067A",
+        "EPOCH": "2020-02-14T04:27:39.231072",
+        "MEAN_MOTION": 15.49165514,
+        "ECCENTRICITY": 0.0004885,
+        "INCLINATION": 51.6443,
+        "RA_OF_ASC_NODE": 242.0161,
+        "ARG_OF_PERICENTER": 264.606,
+        "MEAN_ANOMALY": 207.3845,
+        "EPHEMERIS_TYPE": 0,
+        "CLASSIFICATION_TYPE": "U",
+        "NORAD_CAT_ID": 25544,
+        "ELEMENT_SET_NO": 999,
+        "REV_AT_EPOCH": 21279,
+        "BSTAR": 2.5302e-5,
+        "MEAN_MOTION_DOT": 9.5e-6,
+        "MEAN_MOTION_DDOT": 0
+    }, {
+        "OBJECT_NAME": "GOES 14",
+        "OBJECT_ID": "2009-033A",
+        "EPOCH": "2024-06-17T20:53:42.531360",
+        "MEAN_MOTION": 1.00272898,
+        "ECCENTRICITY": 0.0003981,
+        "INCLINATION": 0.3405,
+        "RA_OF_ASC_NODE": 104.0766,
+        "ARG_OF_PERICENTER": 351.0753,
+        "MEAN_ANOMALY": 16.331,
+        "EPHEMERIS_TYPE": 0,
+        "CLASSIFICATION_TYPE": "U",
+        "NORAD_CAT_ID": 35491,
+        "ELEMENT_SET_NO": 999,
+        "REV_AT_EPOCH": 5480,
+        "BSTAR": 0,
+        "MEAN_MOTION_DOT": -4.4e-7,
+        "MEAN_MOTION_DDOT": 0
+    }]"#;
+
+    #[cfg(feature = "std")]
+    const CSV: &str = "OBJECT_NAME,OBJECT_ID,EPOCH,MEAN_MOTION,ECCENTRICITY,INCLINATION,RA_OF_ASC_NODE,ARG_OF_PERICENTER,MEAN_ANOMALY,EPHEMERIS_TYPE,CLASSIFICATION_TYPE,NORAD_CAT_ID,ELEMENT_SET_NO,REV_AT_EPOCH,BSTAR,MEAN_MOTION_DOT,MEAN_MOTION_DDOT
+ISS (ZARYA),1998-067A,2020-02-14T04:27:39.231072,15.49165514,.0004885,51.6443,242.0161,264.606,207.3845,0,U,25544,999,21279,.25302E-4,.0000095,0
+GOES 14,2009-033A,2024-06-17T20:53:42.531360,1.00272898,.0003981,.3405,104.0766,351.0753,16.331,0,U,35491,999,5480,0,-.00000044,0
+";
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_omm_json_and_csv() {
+        let from_xml: Vec<TLE> = parse_omm_xml(NDM).into_iter().map(Result::unwrap).collect();
+        let from_json: Vec<TLE> = parse_omm_json(JSON).into_iter().map(Result::unwrap).collect();
+        let from_csv: Vec<TLE> = parse_omm_csv(CSV).into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(from_json, from_xml);
+        assert_eq!(from_csv, from_xml);
+        assert_eq!(from_json[0].epoch.to_rfc3339(), "2020-02-14T04:27:39.231072+00:00");
+
+        assert!(matches!(parse_omm_json("No GP data found")[0], Err(OmmError::Syntax(_))));
+        let missing = CSV.replace(",15.49165514,", ",,");
+        assert_eq!(parse_omm_csv(&missing)[0], Err(OmmError::MissingKeyword("MEAN_MOTION")));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_omm_creation_date() {
+        let tle = parse_omm_json(JSON).remove(0).unwrap();
+        let before = Utc::now();
+        let omm = Omm::new(tle.clone());
+        let created = NaiveDateTime::parse_from_str(&omm.header.creation_date, "%Y-%m-%dT%H:%M:%S").unwrap().and_utc();
+        assert!((before - created).num_seconds() < 1 && created <= Utc::now(), "{}", omm.header.creation_date);
+        assert_eq!(Omm::created_at(tle.clone(), tle.epoch).header.creation_date, "2020-02-14T04:27:39");
+    }
+
+    pub(crate) const KVN: &str = "CCSDS_OMM_VERS = 2.0
+COMMENT Generated for testing
+CREATION_DATE = 2024-06-18T00:00:00
+ORIGINATOR = NOAA
+MESSAGE_ID = GOES14_20240618
+
+META_START
+COMMENT GOES 14 metadata
+OBJECT_NAME = GOES 14
+OBJECT_ID = 2009-033A
+CENTER_NAME = EARTH
+REF_FRAME = TEME
+REF_FRAME_EPOCH = 2024-06-17T00:00:00
+TIME_SYSTEM = UTC
+MEAN_ELEMENT_THEORY = SGP4
+META_STOP
+
+COMMENT Mean elements
+EPOCH = 2024-06-17T20:53:42.531360
+MEAN_MOTION = 1.00272898 [rev/day]
+ECCENTRICITY = .0003981
+INCLINATION = .3405 [deg]
+RA_OF_ASC_NODE = 104.0766 [deg]
+ARG_OF_PERICENTER = 351.0753 [deg]
+MEAN_ANOMALY = 16.331 [deg]
+
+EPHEMERIS_TYPE = 0
+CLASSIFICATION_TYPE = U
+NORAD_CAT_ID = 35491
+ELEMENT_SET_NO = 999
+REV_AT_EPOCH = 5480
+BSTAR = 0 [1/ER]
+MEAN_MOTION_DOT = -.00000044 [rev/day**2]
+MEAN_MOTION_DDOT = 0 [rev/day**3]
+
+MASS = 3133 [kg]
+DRAG_COEFF = 2.2
+
+COVARIANCE_START
+COV_REF_FRAME = TEME
+CX_X = 3.331e-04 [km**2]
+CY_X = 4.618e-04 [km**2]
+CY_Y = 6.782e-04 [km**2]
+COVARIANCE_STOP
+
+USER_DEFINED_OPERATOR = NOAA
+USER_DEFINED_MODE = STATION_KEEPING_STOP
+";
+
+    #[test]
+    fn test_parse_omm_kvn() {
+        let omm = parse_omm_kvn(KVN).unwrap();
+        let from_json = parse_omm_json(JSON).remove(1).unwrap();
+
+        assert_eq!(omm.tle, from_json);
+        assert_eq!(omm.header.version, "2.0");
+        assert_eq!(omm.header.originator, "NOAA");
+        assert_eq!(omm.header.message_id.as_deref(), Some("GOES14_20240618"));
+        assert_eq!(omm.header.comments, vec!["Generated for testing"]);
+        assert_eq!(omm.metadata.comments, vec!["GOES 14 metadata"]);
+        assert_eq!(omm.metadata.mean_element_theory, "SGP4");
+        assert_eq!(omm.metadata.ref_frame_epoch.as_deref(), Some("2024-06-17T00:00:00"));
+        assert_eq!(omm.data_comments, vec!["Mean elements"]);
+        assert_eq!(omm.user_defined.get("OPERATOR").map(String::as_str), Some("NOAA"));
+        // a value ending in _STOP isn't a block delimiter
+        assert_eq!(omm.user_defined.get("MODE").map(String::as_str), Some("STATION_KEEPING_STOP"));
+        assert_eq!(omm.spacecraft_parameters.get("MASS").map(String::as_str), Some("3133"));
+        assert_eq!(omm.spacecraft_parameters.get("DRAG_COEFF").map(String::as_str), Some("2.2"));
+        assert_eq!(omm.covariance.len(), 4);
+        assert_eq!(omm.covariance.get("CY_X").map(String::as_str), Some("4.618e-04"));
+
+        assert_eq!(parse_omm_kvn(&KVN.replace("CCSDS_OMM_VERS = 2.0", "")).unwrap_err(), OmmError::MissingKeyword("CCSDS_OMM_VERS"));
+        assert!(matches!(parse_omm_kvn(&KVN.replace("BSTAR = 0", "BSTAR 0")), Err(OmmError::Syntax(_))));
+    }
+}
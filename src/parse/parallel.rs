@@ -0,0 +1,104 @@
+use super::{tle_refs, ParseError, TLE};
+use rayon::prelude::*;
+
+
+// Parses a large string of records across all cores. The input is cut into
+// record-aligned chunks which are split and parsed in parallel, then the
+// results are joined so they come back in input order with the same per
+// record errors as parse_tles. Stray lines are dropped, as in parse_tles.
+pub fn parse_tles_parallel(tles: &str) -> Vec<Result<TLE, ParseError>> {
+    let chunk_size = (tles.len() / (rayon::current_num_threads() * 4)).max(64 * 1024);
+    record_aligned_chunks(tles, chunk_size)
+        .par_iter()
+        .map(|chunk| {
+            tle_refs(chunk)
+                .filter_map(|item| item.ok())
+                .map(|tle| tle.to_tle())
+                .collect::<Vec<Result<TLE, ParseError>>>()
+        })
+        .collect::<Vec<Vec<Result<TLE, ParseError>>>>()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+
+// Cuts the input into chunks of roughly `target` bytes, each ending just
+// after a complete record so no record is split between two chunks.
+fn record_aligned_chunks(input: &str, target: usize) -> Vec<&str> {
+    let mut chunks: Vec<&str> = Vec::new();
+    let mut start = 0;
+    while start < input.len() {
+        let end = next_record_boundary(input, start + target);
+        chunks.push(&input[start..end]);
+        start = end;
+    }
+    chunks
+}
+
+// Finds the start of the first line after `from` that follows a line 2
+// directly preceded by a line 1. Any splitter has just completed a record
+// there, so starting again from that line groups records the same way.
+fn next_record_boundary(input: &str, from: usize) -> usize {
+    if from >= input.len() {
+        return input.len();
+    }
+    let mut position = match input.as_bytes()[from..].iter().position(|&b| b == b'\n') {
+        Some(index) => from + index + 1,
+        None => return input.len(),
+    };
+
+    let mut previous_was_line1 = false;
+    for line in input[position..].split_inclusive('\n') {
+        position += line.len();
+        let text = line.trim();
+        if text.starts_with("2 ") && previous_was_line1 {
+            return position;
+        }
+        if !text.is_empty() {
+            previous_was_line1 = text.starts_with("1 ");
+        }
+    }
+    input.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_tles;
+
+    const TLES: &str = "ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791
+
+1 43890U 18111Q   20044.88470557  .00000320  00000-0  36258-4 0  9993
+2 43890  97.7009 312.6237 0003899   7.8254 352.3026 14.92889838 61757
+STRAY NAME
+GOES 14
+1 35491U 09033A   24169.87063115 -.00000044  00000+0  00000+0 0  9997
+2 35491   0.3405 104.0766 0003981 351.0753  16.3310  1.00272898 54803
+BROKEN
+1 25338U 98030A   24169.93801846  .00000329  00000+0  15393-3 0  9999
+2 25338  98.5680 197.0492 0009520 328.5342  31.5268 14.266054
+";
+
+    #[test]
+    fn test_parse_tles_parallel() {
+        let catalog = TLES.repeat(2000);
+        let parallel = parse_tles_parallel(&catalog);
+        assert_eq!(parallel.len(), 8000);
+        assert_eq!(parallel, parse_tles(catalog));
+    }
+
+    #[test]
+    fn test_record_aligned_chunks() {
+        let catalog = TLES.repeat(10);
+        for target in [1, 50, 200, 1000] {
+            let chunks = record_aligned_chunks(&catalog, target);
+            assert_eq!(chunks.concat(), catalog);
+            for chunk in &chunks[..chunks.len() - 1] {
+                assert!(chunk.lines().last().unwrap().starts_with("2 "));
+            }
+        }
+    }
+}
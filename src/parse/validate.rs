@@ -0,0 +1,221 @@
+use super::{decode_alpha5, ChecksumMismatch, ChecksumMode, ParseError, TleRef, TLE};
+use crate::orbit::EarthModel;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+
+// How much a finding should be trusted. Errors are values that can't
+// describe a real orbit; warnings are elements that are usable but suspect.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+// A problem found by validating a TLE. Validation never fails; callers
+// decide from the findings whether to accept, quarantine or drop a record.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Finding {
+    // Line 1 or 2 doesn't start with its line number.
+    WrongLineNumber { line: usize, found: String },
+    // Lines 1 and 2 carry different catalog numbers (columns 3-7).
+    CatalogNumberMismatch { line1: String, line2: String },
+    // The checksum in column 69 doesn't match the line.
+    ChecksumMismatch(ChecksumMismatch),
+    // Eccentricity outside 0 <= e < 1, so the orbit isn't closed.
+    EccentricityOutOfRange(f64),
+    // Inclination outside 0-180 degrees.
+    InclinationOutOfRange(f64),
+    // Mean motion of zero or less, or not a number.
+    NonPositiveMeanMotion(f64),
+    // Right ascension, argument of perigee or mean anomaly outside 0-360 degrees.
+    AngleOutOfRange { field: &'static str, value: f64 },
+    // The perigee is below the Earth's equatorial radius, in km of altitude.
+    PerigeeBelowSurface(f64),
+    // A field couldn't be parsed, so the elements weren't checked.
+    Unparseable(ParseError),
+}
+
+impl Finding {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Finding::ChecksumMismatch(_) | Finding::PerigeeBelowSurface(_) => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl Display for Finding {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result {
+        match self {
+            Finding::WrongLineNumber { line, found } => {
+                write!(formatter, "line {} starts with {:?}", line, found)
+            }
+            Finding::CatalogNumberMismatch { line1, line2 } => {
+                write!(formatter, "catalog number {:?} on line 1 but {:?} on line 2", line1, line2)
+            }
+            Finding::ChecksumMismatch(mismatch) => write!(
+                formatter,
+                "line {} checksum is {:?}, expected {}",
+                mismatch.line, mismatch.found, mismatch.expected
+            ),
+            Finding::EccentricityOutOfRange(value) => write!(formatter, "eccentricity {} is not in [0, 1)", value),
+            Finding::InclinationOutOfRange(value) => write!(formatter, "inclination {} is not in [0, 180]", value),
+            Finding::NonPositiveMeanMotion(value) => write!(formatter, "mean motion {} is not positive", value),
+            Finding::AngleOutOfRange { field, value } => write!(formatter, "{} {} is not in [0, 360]", field, value),
+            Finding::PerigeeBelowSurface(altitude) => write!(formatter, "perigee altitude {:.1} km is below the surface", altitude),
+            Finding::Unparseable(e) => write!(formatter, "{}", e),
+        }
+    }
+}
+
+
+impl TLE {
+
+    // Checks the elements are physically plausible. Line level checks need
+    // the original text, see TleRef::validate.
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = self
+            .checksum_mismatches
+            .iter()
+            .cloned()
+            .map(Finding::ChecksumMismatch)
+            .collect();
+
+        if !(0.0..1.0).contains(&self.eccentricity) {
+            findings.push(Finding::EccentricityOutOfRange(self.eccentricity));
+        }
+        if !(0.0..=180.0).contains(&self.inclination) {
+            findings.push(Finding::InclinationOutOfRange(self.inclination));
+        }
+        let mean_motion_valid = self.mean_motion > 0.0 && self.mean_motion.is_finite();
+        if !mean_motion_valid {
+            findings.push(Finding::NonPositiveMeanMotion(self.mean_motion));
+        }
+        for (field, value) in [
+            ("right_ascension", self.right_ascension),
+            ("argument_of_perigee", self.argument_of_perigee),
+            ("mean_anomaly", self.mean_anomaly),
+        ] {
+            // 360 is accepted as format_tle writes it
+            if !(0.0..=360.0).contains(&value) {
+                findings.push(Finding::AngleOutOfRange { field, value });
+            }
+        }
+
+        if mean_motion_valid && findings.iter().all(|finding| finding.severity() < Severity::Error) {
+            let perigee = self.perigee_altitude(EarthModel::Wgs72);
+            if perigee < 0.0 {
+                findings.push(Finding::PerigeeBelowSurface(perigee));
+            }
+        }
+        findings
+    }
+}
+
+impl TleRef<'_> {
+
+    // Checks the line numbers, that both lines carry the same catalog
+    // number and the checksums, then, if every field parses, the same
+    // plausibility checks as TLE::validate. A field that doesn't parse is
+    // an Unparseable finding.
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = Vec::new();
+
+        for (line, text, expected) in [(1, self.line1(), "1"), (2, self.line2(), "2")] {
+            let found = text.get(0..1).unwrap_or("");
+            if found != expected {
+                findings.push(Finding::WrongLineNumber { line, found: found.to_string() });
+            }
+        }
+
+        let line1 = self.line1().get(2..7).unwrap_or("").trim();
+        let line2 = self.line2().get(2..7).unwrap_or("").trim();
+        let same = match (decode_alpha5(line1), decode_alpha5(line2)) {
+            (Some(number1), Some(number2)) => number1 == number2,
+            _ => line1 == line2,
+        };
+        if !same {
+            findings.push(Finding::CatalogNumberMismatch { line1: line1.to_string(), line2: line2.to_string() });
+        }
+
+        match self.to_tle_with_checksum(ChecksumMode::Warn) {
+            Ok(tle) => findings.extend(tle.validate()),
+            Err(e) => {
+                if let Ok(mismatches) = self.checksum_mismatches(ChecksumMode::Warn) {
+                    findings.extend(mismatches.into_iter().map(Finding::ChecksumMismatch));
+                }
+                findings.push(Finding::Unparseable(e));
+            }
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_tle;
+
+    const ISS: &str = "ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791";
+
+    #[test]
+    fn test_valid_tle() {
+        assert_eq!(TleRef::new(ISS).unwrap().validate(), vec![]);
+        assert_eq!(parse_tle(ISS).unwrap().validate(), vec![]);
+    }
+
+    #[test]
+    fn test_line_findings() {
+        let mismatched = ISS.replace("2 25544  51", "2 25545  51");
+        let findings = TleRef::new(&mismatched).unwrap().validate();
+        assert_eq!(findings[0], Finding::CatalogNumberMismatch { line1: "25544".to_string(), line2: "25545".to_string() });
+        assert!(matches!(findings[1], Finding::ChecksumMismatch(ChecksumMismatch { line: 2, .. })));
+        assert_eq!(findings[0].severity(), Severity::Error);
+        assert_eq!(findings[1].severity(), Severity::Warning);
+
+        let swapped = TleRef::from_lines("", ISS.lines().nth(2).unwrap(), ISS.lines().nth(1).unwrap());
+        let findings = swapped.validate();
+        assert_eq!(findings[0], Finding::WrongLineNumber { line: 1, found: "2".to_string() });
+        assert_eq!(findings[1], Finding::WrongLineNumber { line: 2, found: "1".to_string() });
+    }
+
+    #[test]
+    fn test_unparseable() {
+        // the checksums still match, so the parse error is the only finding
+        let lines: Vec<&str> = ISS.lines().collect();
+        let line2 = lines[2].replace("51.6443", "51.64x3");
+        let line2 = format!("{}{}", &line2[..68], crate::parse::compute_checksum(&line2[..68]));
+        let findings = TleRef::from_lines(lines[0], lines[1], &line2).validate();
+        assert!(matches!(&findings[..], [Finding::Unparseable(e)] if e.field == "inclination"), "{:?}", findings);
+        assert_eq!(findings[0].severity(), Severity::Error);
+    }
+
+    #[test]
+    fn test_plausibility_findings() {
+        let mut tle = parse_tle(ISS).unwrap();
+        tle.eccentricity = 1.2;
+        tle.inclination = 181.0;
+        tle.right_ascension = 360.0;
+        tle.mean_anomaly = 360.5;
+        assert_eq!(tle.validate(), vec![
+            Finding::EccentricityOutOfRange(1.2),
+            Finding::InclinationOutOfRange(181.0),
+            Finding::AngleOutOfRange { field: "mean_anomaly", value: 360.5 },
+        ]);
+
+        let mut tle = parse_tle(ISS).unwrap();
+        tle.mean_motion = 0.0;
+        assert_eq!(tle.validate(), vec![Finding::NonPositiveMeanMotion(0.0)]);
+
+        // 17.5 rev/day puts the orbit inside the Earth
+        tle.mean_motion = 17.5;
+        let findings = tle.validate();
+        assert!(matches!(findings[..], [Finding::PerigeeBelowSurface(altitude)] if altitude < 0.0));
+        assert_eq!(findings.iter().map(Finding::severity).max(), Some(Severity::Warning));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::parse::TLE;
+use crate::sgp4::{Sgp4, Sgp4Error};
+use crate::station::{GroundStation, LookAngles};
+use alloc::vec::Vec;
+use chrono::{DateTime, Duration, Utc};
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::math::Float;
+
+
+// AOS, LOS and culmination are found to within this many seconds.
+const PRECISION: f64 = 1.0;
+// The search samples the elevation this many times per orbit, within the
+// bounds below, and looks between samples for passes that rise and set
+// between them.
+const SAMPLES_PER_ORBIT: f64 = 200.0;
+const MIN_STEP: f64 = 10.0;
+const MAX_STEP: f64 = 600.0;
+
+// A period when a satellite is above both the minimum elevation and the
+// station's horizon mask. AOS is the first and LOS the last visible time.
+// When the satellite is already visible at the start of the search window
+// (or still visible at its end) AOS (or LOS) is that edge of the window,
+// so a geostationary satellite in view is one pass covering the window.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Pass {
+    pub satellite_number: u32,
+    pub aos: DateTime<Utc>,
+    pub los: DateTime<Utc>,
+    pub culmination: DateTime<Utc>,
+    pub max_elevation: f64,
+    pub visible_at_start: bool,
+    pub visible_at_end: bool,
+}
+
+impl Pass {
+    pub fn duration(&self) -> Duration {
+        self.los - self.aos
+    }
+
+    // Whether the satellite was in view for the whole search window.
+    pub fn is_always_visible(&self) -> bool {
+        self.visible_at_start && self.visible_at_end
+    }
+}
+
+impl GroundStation {
+
+    // The passes of the satellite between start and end above the minimum
+    // elevation in degrees, in time order.
+    pub fn passes(&self, tle: &TLE, start: DateTime<Utc>, end: DateTime<Utc>, minimum_elevation: f64) -> Result<Vec<Pass>, Sgp4Error> {
+        let search = Search {station: self, sgp4: Sgp4::new(tle)?, start, minimum_elevation};
+        let step = (tle.period() * 60.0 / SAMPLES_PER_ORBIT).clamp(MIN_STEP, MAX_STEP);
+        let window = (end - start).num_milliseconds() as f64 / 1000.0;
+        if window <= 0.0 {
+            return Ok(Vec::new());
+        }
+
+        let count = (window / step).ceil() as usize;
+        let mut samples = Vec::with_capacity(count + 1);
+        for i in 0..=count {
+            let seconds = (i as f64 * step).min(window);
+            samples.push((seconds, search.margin(seconds)?));
+        }
+
+        let mut passes = Vec::new();
+        let mut rise = (samples[0].1 >= 0.0).then_some(0.0);
+        for i in 1..samples.len() {
+            let ((before, before_margin), (after, after_margin)) = (samples[i - 1], samples[i]);
+            match rise {
+                None if after_margin >= 0.0 => rise = Some(search.crossing(before, after)?),
+                Some(aos) if after_margin < 0.0 => {
+                    let los = search.crossing(after, before)?;
+                    passes.push(search.pass(tle.satellite_number, aos, los, window, step)?);
+                    rise = None;
+                }
+                None if i >= 2 && samples[i - 2].1 < before_margin && before_margin >= after_margin => {
+                    // the margin peaked below zero at the sample, but may
+                    // have risen above it between samples
+                    let earlier = samples[i - 2].0;
+                    let (peak, margin) = maximise(earlier, after, |seconds| search.margin(seconds))?;
+                    if margin >= 0.0 {
+                        let aos = search.crossing(earlier, peak)?;
+                        let los = search.crossing(after, peak)?;
+                        passes.push(search.pass(tle.satellite_number, aos, los, window, step)?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(aos) = rise {
+            passes.push(search.pass(tle.satellite_number, aos, window, window, step)?);
+        }
+        Ok(passes)
+    }
+}
+
+struct Search<'a> {
+    station: &'a GroundStation,
+    sgp4: Sgp4,
+    start: DateTime<Utc>,
+    minimum_elevation: f64,
+}
+
+impl Search<'_> {
+
+    fn time(&self, seconds: f64) -> DateTime<Utc> {
+        self.start + Duration::nanoseconds((seconds * 1e9).round() as i64)
+    }
+
+    fn look_angles(&self, seconds: f64) -> Result<LookAngles, Sgp4Error> {
+        self.station.observe(&self.sgp4, self.time(seconds))
+    }
+
+    // Degrees above the higher of the minimum elevation and the horizon
+    // mask, negative when the satellite can't be seen.
+    fn margin(&self, seconds: f64) -> Result<f64, Sgp4Error> {
+        let look_angles = self.look_angles(seconds)?;
+        let threshold = self.minimum_elevation.max(self.station.minimum_elevation(look_angles.azimuth));
+        Ok(look_angles.elevation - threshold)
+    }
+
+    // Bisects between a time the satellite is hidden and one it is
+    // visible, returning the visible end once they are within PRECISION.
+    fn crossing(&self, mut hidden: f64, mut visible: f64) -> Result<f64, Sgp4Error> {
+        while (visible - hidden).abs() > PRECISION {
+            let middle = (hidden + visible) / 2.0;
+            if self.margin(middle)? >= 0.0 {
+                visible = middle;
+            } else {
+                hidden = middle;
+            }
+        }
+        Ok(visible)
+    }
+
+    // Finds the culmination by sampling at the search step and refining
+    // around the highest sample.
+    fn pass(&self, satellite_number: u32, aos: f64, los: f64, window: f64, step: f64) -> Result<Pass, Sgp4Error> {
+        let elevation = |seconds: f64| Ok(self.look_angles(seconds)?.elevation);
+        let mut highest = (aos, elevation(aos)?);
+        let mut seconds = aos;
+        while seconds < los {
+            seconds = (seconds + step).min(los);
+            let sample = (seconds, elevation(seconds)?);
+            if sample.1 > highest.1 {
+                highest = sample;
+            }
+        }
+        let (culmination, max_elevation) = maximise((highest.0 - step).max(aos), (highest.0 + step).min(los), elevation)?;
+        let (culmination, max_elevation) = if max_elevation > highest.1 {(culmination, max_elevation)} else {highest};
+
+        Ok(Pass {
+            satellite_number,
+            aos: self.time(aos),
+            los: self.time(los),
+            culmination: self.time(culmination),
+            max_elevation,
+            visible_at_start: aos == 0.0,
+            visible_at_end: los == window,
+        })
+    }
+}
+
+// Golden section search for the maximum of a function that rises then
+// falls between a and b, to within PRECISION.
+fn maximise<F>(mut a: f64, mut b: f64, mut function: F) -> Result<(f64, f64), Sgp4Error>
+where
+    F: FnMut(f64) -> Result<f64, Sgp4Error>,
+{
+    const RATIO: f64 = 0.6180339887498949;
+    let mut lower = b - RATIO * (b - a);
+    let mut upper = a + RATIO * (b - a);
+    let (mut lower_value, mut upper_value) = (function(lower)?, function(upper)?);
+    while b - a > PRECISION {
+        if lower_value < upper_value {
+            a = lower;
+            (lower, lower_value) = (upper, upper_value);
+            upper = a + RATIO * (b - a);
+            upper_value = function(upper)?;
+        } else {
+            b = upper;
+            (upper, upper_value) = (lower, lower_value);
+            lower = b - RATIO * (b - a);
+            lower_value = function(lower)?;
+        }
+    }
+    Ok(if lower_value < upper_value {(upper, upper_value)} else {(lower, lower_value)})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::Geodetic;
+    use crate::parse::parse_tle;
+
+    #[test]
+    fn test_passes() {
+        let iss = parse_tle("ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap();
+        let station = GroundStation::new(Geodetic {latitude: 51.48, longitude: -0.01, altitude: 0.05})
+            .with_horizon_mask(vec![(0.0, 2.0), (180.0, 8.0)]);
+        let minimum_elevation = 5.0;
+        let (start, end) = (iss.epoch, iss.epoch + Duration::days(1));
+        let passes = station.passes(&iss, start, end, minimum_elevation).unwrap();
+
+        // compare with checking every second
+        let sgp4 = Sgp4::new(&iss).unwrap();
+        let mut expected = Vec::new();
+        let mut aos = None;
+        for second in 0..=86400 {
+            let look_angles = station.observe(&sgp4, start + Duration::seconds(second)).unwrap();
+            let visible = station.is_visible(&look_angles) && look_angles.elevation >= minimum_elevation;
+            match aos {
+                None if visible => aos = Some(look_angles.time),
+                Some(time) if !visible => {
+                    expected.push((time, look_angles.time - Duration::seconds(1)));
+                    aos = None;
+                }
+                _ => {}
+            }
+        }
+
+        assert!(expected.len() >= 3);
+        assert_eq!(passes.len(), expected.len());
+        for (pass, (aos, los)) in passes.iter().zip(expected) {
+            assert_eq!(pass.satellite_number, 25544);
+            assert!((pass.aos - aos).num_milliseconds().abs() <= 1000, "{:?} {}", pass, aos);
+            assert!((pass.los - los).num_milliseconds().abs() <= 1000, "{:?} {}", pass, los);
+            assert!(pass.aos < pass.culmination && pass.culmination < pass.los, "{:?}", pass);
+            assert!(!pass.visible_at_start && !pass.visible_at_end);
+
+            let culmination = station.observe(&sgp4, pass.culmination).unwrap();
+            assert_eq!(culmination.elevation, pass.max_elevation);
+            for seconds in (-30..=30).step_by(5) {
+                let nearby = station.observe(&sgp4, pass.culmination + Duration::seconds(seconds)).unwrap();
+                assert!(nearby.elevation <= pass.max_elevation + 1e-3, "{:?} {:?}", pass, nearby);
+            }
+        }
+    }
+
+    #[test]
+    fn test_geostationary() {
+        let insat = parse_tle("INSAT-3DS
+1 58990U 24033A   24166.45222727 -.00000153  00000+0  00000+0 0  9991
+2 58990   0.0993  93.3619 0011635 357.7741  57.9093  1.00271183   944").unwrap();
+        let below = Sgp4::new(&insat).unwrap().propagate_geodetic(insat.epoch, None).unwrap();
+        let (start, end) = (insat.epoch, insat.epoch + Duration::days(2));
+
+        let station = GroundStation::new(Geodetic {latitude: 20.0, longitude: below.longitude + 10.0, altitude: 0.0});
+        let passes = station.passes(&insat, start, end, 10.0).unwrap();
+        assert_eq!(passes.len(), 1);
+        let pass = passes[0];
+        assert!(pass.is_always_visible());
+        assert_eq!((pass.aos, pass.los), (start, end));
+        assert_eq!(pass.duration(), Duration::days(2));
+        assert!((40.0..70.0).contains(&pass.max_elevation), "{:?}", pass);
+
+        // never visible from the other side of the Earth
+        let antipode = GroundStation::new(Geodetic {latitude: -20.0, longitude: below.longitude - 170.0, altitude: 0.0});
+        assert!(antipode.passes(&insat, start, end, 0.0).unwrap().is_empty());
+        assert!(station.passes(&insat, end, start, 0.0).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,625 @@
+use crate::orbit::{EarthConstants, EarthModel};
+use crate::parse::TLE;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use core::f64::consts::{PI, TAU};
+use core::fmt::{Display, Formatter};
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::math::Float;
+
+mod deep_space;
+
+use deep_space::{DeepSpace, Elements, Initial};
+
+
+const MINUTES_PER_DAY: f64 = 1440.0;
+const TWO_THIRDS: f64 = 2.0 / 3.0;
+
+// Orbits with a period of at least this many minutes are propagated with
+// the deep-space (SDP4) equations.
+pub const DEEP_SPACE_PERIOD: f64 = 225.0;
+
+// A position in km and velocity in km/s in the True Equator Mean Equinox
+// frame of the TLE's epoch, the frame SGP4 works in.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct State {
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+}
+
+// Why the elements couldn't be propagated. The codes match the error
+// numbers of Vallado's reference implementation.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Sgp4Error {
+    // The mean eccentricity left 0 <= e < 1, usually from drag. Code 1.
+    MeanEccentricityOutOfRange(f64),
+    // The mean motion is zero or negative. Code 2.
+    NonPositiveMeanMotion(f64),
+    // The eccentricity after the periodic terms left 0 <= e <= 1. Code 3.
+    PerturbedEccentricityOutOfRange(f64),
+    // The osculating orbit isn't an ellipse. Code 4.
+    NegativeSemiLatusRectum(f64),
+    // The orbit is below the Earth's surface, in Earth radii. Code 6.
+    Decayed(f64),
+    // The time since epoch, in minutes, is infinite or not a number. The
+    // reference implementation doesn't check this. Code 7.
+    NonFiniteTime(f64),
+}
+
+impl Sgp4Error {
+    pub fn code(&self) -> u8 {
+        match self {
+            Sgp4Error::MeanEccentricityOutOfRange(_) => 1,
+            Sgp4Error::NonPositiveMeanMotion(_) => 2,
+            Sgp4Error::PerturbedEccentricityOutOfRange(_) => 3,
+            Sgp4Error::NegativeSemiLatusRectum(_) => 4,
+            Sgp4Error::Decayed(_) => 6,
+            Sgp4Error::NonFiniteTime(_) => 7,
+        }
+    }
+}
+
+impl Display for Sgp4Error {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::core::fmt::Result {
+        match self {
+            Sgp4Error::MeanEccentricityOutOfRange(value) => write!(formatter, "mean eccentricity {} is not in [0, 1)", value),
+            Sgp4Error::NonPositiveMeanMotion(value) => write!(formatter, "mean motion {} is not positive", value),
+            Sgp4Error::PerturbedEccentricityOutOfRange(value) => write!(formatter, "perturbed eccentricity {} is not in [0, 1]", value),
+            Sgp4Error::NegativeSemiLatusRectum(value) => write!(formatter, "semi-latus rectum {} is negative", value),
+            Sgp4Error::Decayed(radius) => write!(formatter, "orbit radius {} Earth radii is below the surface", radius),
+            Sgp4Error::NonFiniteTime(tsince) => write!(formatter, "time since epoch {} minutes is not finite", tsince),
+        }
+    }
+}
+
+impl core::error::Error for Sgp4Error {}
+
+
+// An SGP4 propagator initialised from a TLE, switching to SDP4 for periods
+// of 225 minutes or more. This follows Vallado's sgp4unit (the 2006
+// revision of Spacetrack Report #3, with its later fixes) in AFSPC
+// compatibility mode, and keeps its variable names so the two can be read
+// side by side. Units are Earth radii and minutes unless noted.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Sgp4 {
+    epoch: DateTime<Utc>,
+    radius: f64,
+    j2: f64,
+    j3oj2: f64,
+    xke: f64,
+
+    bstar: f64,
+    ecco: f64,
+    argpo: f64,
+    inclo: f64,
+    mo: f64,
+    nodeo: f64,
+    no_unkozai: f64,
+
+    // Perigees below 220 km use a truncated drag model.
+    isimp: bool,
+    aycof: f64,
+    con41: f64,
+    cc1: f64,
+    cc4: f64,
+    cc5: f64,
+    d2: f64,
+    d3: f64,
+    d4: f64,
+    delmo: f64,
+    eta: f64,
+    argpdot: f64,
+    omgcof: f64,
+    sinmao: f64,
+    t2cof: f64,
+    t3cof: f64,
+    t4cof: f64,
+    t5cof: f64,
+    x1mth2: f64,
+    x7thm1: f64,
+    mdot: f64,
+    nodedot: f64,
+    xlcof: f64,
+    xmcof: f64,
+    nodecf: f64,
+
+    // Present for periods of 225 minutes or more.
+    deep_space: Option<DeepSpace>,
+}
+
+impl Sgp4 {
+
+    // Initialises the propagator with the WGS-72 constants the TLE was
+    // fitted with.
+    pub fn new(tle: &TLE) -> Result<Self, Sgp4Error> {
+        Sgp4::with_model(tle, EarthModel::Wgs72)
+    }
+
+    // Initialises the propagator and checks the elements propagate at
+    // their epoch.
+    pub fn with_model(tle: &TLE, model: EarthModel) -> Result<Self, Sgp4Error> {
+        if !(0.0..1.0).contains(&tle.eccentricity) {
+            return Err(Sgp4Error::MeanEccentricityOutOfRange(tle.eccentricity));
+        }
+        if tle.mean_motion <= 0.0 || !tle.mean_motion.is_finite() {
+            return Err(Sgp4Error::NonPositiveMeanMotion(tle.mean_motion));
+        }
+        let sgp4 = Sgp4::initialise(tle, model.constants())?;
+        sgp4.propagate(0.0)?;
+        Ok(sgp4)
+    }
+
+    pub fn epoch(&self) -> DateTime<Utc> {
+        self.epoch
+    }
+
+    // Whether the orbit is propagated with the deep-space equations.
+    pub fn is_deep_space(&self) -> bool {
+        self.deep_space.is_some()
+    }
+
+    // Propagates to a time, see propagate.
+    pub fn propagate_to(&self, time: DateTime<Utc>) -> Result<State, Sgp4Error> {
+        self.propagate(minutes_since(self.epoch, time))
+    }
+
+    // Propagates the elements by a number of minutes from their epoch,
+    // which may be negative.
+    pub fn propagate(&self, tsince: f64) -> Result<State, Sgp4Error> {
+        // the deep-space resonance integrator would never reach the time
+        if !tsince.is_finite() {
+            return Err(Sgp4Error::NonFiniteTime(tsince));
+        }
+        let t = tsince;
+
+        // secular gravity and atmospheric drag
+        let xmdf = self.mo + self.mdot * t;
+        let argpdf = self.argpo + self.argpdot * t;
+        let nodedf = self.nodeo + self.nodedot * t;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = t * t;
+        let mut nodem = nodedf + self.nodecf * t2;
+        let mut tempa = 1.0 - self.cc1 * t;
+        let mut tempe = self.bstar * self.cc4 * t;
+        let mut templ = self.t2cof * t2;
+
+        if !self.isimp {
+            let delomg = self.omgcof * t;
+            let delmtemp = 1.0 + self.eta * xmdf.cos();
+            let delm = self.xmcof * (delmtemp * delmtemp * delmtemp - self.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa = tempa - self.d2 * t2 - self.d3 * t3 - self.d4 * t4;
+            tempe += self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ += self.t3cof * t3 + t4 * (self.t4cof + t * self.t5cof);
+        }
+
+        let mut nm = self.no_unkozai;
+        let mut em = self.ecco;
+        let mut inclm = self.inclo;
+        if let Some(deep_space) = &self.deep_space {
+            let mut elements = Elements { em, inclm, nodem, argpm, mm, nm };
+            deep_space.secular(t, &mut elements);
+            Elements { em, inclm, nodem, argpm, mm, nm } = elements;
+        }
+        if nm <= 0.0 {
+            return Err(Sgp4Error::NonPositiveMeanMotion(nm));
+        }
+
+        let am = (self.xke / nm).powf(TWO_THIRDS) * tempa * tempa;
+        let nm = self.xke / am.powf(1.5);
+        em -= tempe;
+        if !(-0.001..1.0).contains(&em) {
+            return Err(Sgp4Error::MeanEccentricityOutOfRange(em));
+        }
+        if em < 1.0e-6 {
+            em = 1.0e-6;
+        }
+        mm += self.no_unkozai * templ;
+        let mut xlm = mm + argpm + nodem;
+        nodem %= TAU;
+        argpm %= TAU;
+        xlm %= TAU;
+        mm = (xlm - argpm - nodem) % TAU;
+
+        // lunar-solar periodics
+        let mut ep = em;
+        let mut xincp = inclm;
+        let mut argpp = argpm;
+        let mut nodep = nodem;
+        let mut mp = mm;
+        if let Some(deep_space) = &self.deep_space {
+            let mut elements = Elements { em: ep, inclm: xincp, nodem: nodep, argpm: argpp, mm: mp, nm };
+            deep_space.periodics(t, &mut elements);
+            Elements { em: ep, inclm: xincp, nodem: nodep, argpm: argpp, mm: mp, .. } = elements;
+            if xincp < 0.0 {
+                xincp = -xincp;
+                nodep += PI;
+                argpp -= PI;
+            }
+            if !(0.0..=1.0).contains(&ep) {
+                return Err(Sgp4Error::PerturbedEccentricityOutOfRange(ep));
+            }
+        }
+
+        // long period periodics
+        let sinip = xincp.sin();
+        let cosip = xincp.cos();
+        let (aycof, xlcof) = match self.deep_space {
+            Some(_) => (-0.5 * self.j3oj2 * sinip, long_period_coefficient(self.j3oj2, sinip, cosip)),
+            None => (self.aycof, self.xlcof),
+        };
+
+        let axnl = ep * argpp.cos();
+        let temp = 1.0 / (am * (1.0 - ep * ep));
+        let aynl = ep * argpp.sin() + temp * aycof;
+        let xl = mp + argpp + nodep + temp * xlcof * axnl;
+
+        // Kepler's equation
+        let u = (xl - nodep) % TAU;
+        let mut eo1 = u;
+        let mut tem5: f64 = 9999.9;
+        let mut ktr = 1;
+        let (mut sineo1, mut coseo1) = (0.0, 0.0);
+        while tem5.abs() >= 1.0e-12 && ktr <= 10 {
+            sineo1 = eo1.sin();
+            coseo1 = eo1.cos();
+            tem5 = 1.0 - coseo1 * axnl - sineo1 * aynl;
+            tem5 = (u - aynl * coseo1 + axnl * sineo1 - eo1) / tem5;
+            tem5 = tem5.clamp(-0.95, 0.95);
+            eo1 += tem5;
+            ktr += 1;
+        }
+
+        // short period preliminary quantities
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        if pl < 0.0 {
+            return Err(Sgp4Error::NegativeSemiLatusRectum(pl));
+        }
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let mut su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * self.j2 * temp;
+        let temp2 = temp1 * temp;
+
+        let (con41, x1mth2, x7thm1) = match self.deep_space {
+            Some(_) => {
+                let cosisq = cosip * cosip;
+                (3.0 * cosisq - 1.0, 1.0 - cosisq, 7.0 * cosisq - 1.0)
+            }
+            None => (self.con41, self.x1mth2, self.x7thm1),
+        };
+
+        // short period periodics
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * con41) + 0.5 * temp1 * x1mth2 * cos2u;
+        su -= 0.25 * temp2 * x7thm1 * sin2u;
+        let xnode = nodep + 1.5 * temp2 * cosip * sin2u;
+        let xinc = xincp + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * x1mth2 * sin2u / self.xke;
+        let rvdot = rvdotl + nm * temp1 * (x1mth2 * cos2u + 1.5 * con41) / self.xke;
+
+        // orientation vectors
+        let (sinsu, cossu) = (su.sin(), su.cos());
+        let (snod, cnod) = (xnode.sin(), xnode.cos());
+        let (sini, cosi) = (xinc.sin(), xinc.cos());
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let u = [xmx * sinsu + cnod * cossu, xmy * sinsu + snod * cossu, sini * sinsu];
+        let v = [xmx * cossu - cnod * sinsu, xmy * cossu - snod * sinsu, sini * cossu];
+
+        if mrt < 1.0 {
+            return Err(Sgp4Error::Decayed(mrt));
+        }
+        let vkmpersec = self.radius * self.xke / 60.0;
+        Ok(State {
+            position: [0, 1, 2].map(|i| mrt * u[i] * self.radius),
+            velocity: [0, 1, 2].map(|i| (mvt * u[i] + rvdot * v[i]) * vkmpersec),
+        })
+    }
+
+    // Vallado's initl and sgp4init.
+    fn initialise(tle: &TLE, constants: EarthConstants) -> Result<Self, Sgp4Error> {
+        let radius = constants.radius;
+        let xke = 60.0 / (radius * radius * radius / constants.mu).sqrt();
+        let j2 = constants.j2;
+        let j3oj2 = constants.j3 / j2;
+        let j4 = constants.j4;
+
+        let bstar = tle.drag_term;
+        let ecco = tle.eccentricity;
+        let argpo = tle.argument_of_perigee.to_radians();
+        let inclo = tle.inclination.to_radians();
+        let mo = tle.mean_anomaly.to_radians();
+        let nodeo = tle.right_ascension.to_radians();
+        let no_kozai = tle.mean_motion * TAU / MINUTES_PER_DAY;
+
+        // recover the Brouwer mean motion from the Kozai one in the TLE
+        let eccsq = ecco * ecco;
+        let omeosq = 1.0 - eccsq;
+        let rteosq = omeosq.sqrt();
+        let cosio = inclo.cos();
+        let cosio2 = cosio * cosio;
+        let ak = (xke / no_kozai).powf(TWO_THIRDS);
+        let d1 = 0.75 * j2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let del = d1 / (ak * ak);
+        let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        let del = d1 / (adel * adel);
+        let no_unkozai = no_kozai / (1.0 + del);
+
+        let ao = (xke / no_unkozai).powf(TWO_THIRDS);
+        let sinio = inclo.sin();
+        let po = ao * omeosq;
+        let con42 = 1.0 - 5.0 * cosio2;
+        let con41 = -con42 - cosio2 - cosio2;
+        let posq = po * po;
+        let rp = ao * (1.0 - ecco);
+
+        // the atmosphere's density parameters, lowered for low perigees
+        let mut isimp = rp < 220.0 / radius + 1.0;
+        let mut sfour = 78.0 / radius + 1.0;
+        let mut qzms24 = ((120.0 - 78.0) / radius).powi(4);
+        let perige = (rp - 1.0) * radius;
+        if perige < 156.0 {
+            sfour = if perige < 98.0 { 20.0 } else { perige - 78.0 };
+            qzms24 = ((120.0 - sfour) / radius).powi(4);
+            sfour = sfour / radius + 1.0;
+        }
+
+        let pinvsq = 1.0 / posq;
+        let tsi = 1.0 / (ao - sfour);
+        let eta = ao * ecco * tsi;
+        let etasq = eta * eta;
+        let eeta = ecco * eta;
+        let psisq = (1.0 - etasq).abs();
+        let coef = qzms24 * tsi.powi(4);
+        let coef1 = coef / psisq.powf(3.5);
+        let cc2 = coef1 * no_unkozai * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+            + 0.375 * j2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let cc1 = bstar * cc2;
+        let cc3 = if ecco > 1.0e-4 { -2.0 * coef * tsi * j3oj2 * no_unkozai * sinio / ecco } else { 0.0 };
+        let x1mth2 = 1.0 - cosio2;
+        let cc4 = 2.0 * no_unkozai * coef1 * ao * omeosq * (eta * (2.0 + 0.5 * etasq) + ecco * (0.5 + 2.0 * etasq)
+            - j2 * tsi / (ao * psisq) * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+                + 0.75 * x1mth2 * (2.0 * etasq - eeta * (1.0 + etasq)) * (2.0 * argpo).cos()));
+        let cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+
+        let cosio4 = cosio2 * cosio2;
+        let temp1 = 1.5 * j2 * pinvsq * no_unkozai;
+        let temp2 = 0.5 * temp1 * j2 * pinvsq;
+        let temp3 = -0.46875 * j4 * pinvsq * pinvsq * no_unkozai;
+        let mdot = no_unkozai + 0.5 * temp1 * rteosq * con41
+            + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot = -0.5 * temp1 * con42 + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1 = -temp1 * cosio;
+        let nodedot = xhdot1 + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+        let omgcof = bstar * cc3 * argpo.cos();
+        let xmcof = if ecco > 1.0e-4 { -TWO_THIRDS * coef * bstar / eeta } else { 0.0 };
+        let nodecf = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof = 1.5 * cc1;
+        let xlcof = long_period_coefficient(j3oj2, sinio, cosio);
+        let aycof = -0.5 * j3oj2 * sinio;
+        let delmo = (1.0 + eta * mo.cos()).powi(3);
+        let sinmao = mo.sin();
+        let x7thm1 = 7.0 * cosio2 - 1.0;
+
+        let mut deep_space = None;
+        if TAU / no_unkozai >= DEEP_SPACE_PERIOD {
+            isimp = true;
+            deep_space = Some(DeepSpace::new(&Initial {
+                epoch: afspc_epoch(tle.epoch),
+                xke,
+                ecco,
+                argpo,
+                inclo,
+                nodeo,
+                mo,
+                no: no_unkozai,
+                mdot,
+                argpdot,
+                nodedot,
+            }));
+        }
+
+        let (mut d2, mut d3, mut d4, mut t3cof, mut t4cof, mut t5cof) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        if !isimp {
+            let cc1sq = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2 * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        Ok(Sgp4 {
+            epoch: tle.epoch,
+            radius,
+            j2,
+            j3oj2,
+            xke,
+            bstar,
+            ecco,
+            argpo,
+            inclo,
+            mo,
+            nodeo,
+            no_unkozai,
+            isimp,
+            aycof,
+            con41,
+            cc1,
+            cc4,
+            cc5,
+            d2,
+            d3,
+            d4,
+            delmo,
+            eta,
+            argpdot,
+            omgcof,
+            sinmao,
+            t2cof,
+            t3cof,
+            t4cof,
+            t5cof,
+            x1mth2,
+            x7thm1,
+            mdot,
+            nodedot,
+            xlcof,
+            xmcof,
+            nodecf,
+            deep_space,
+        })
+    }
+}
+
+// The J3 long period coefficient of the mean longitude, guarded against
+// the division by zero at 180 degrees inclination.
+fn long_period_coefficient(j3oj2: f64, sinio: f64, cosio: f64) -> f64 {
+    let denominator = if (cosio + 1.0).abs() > 1.5e-12 { 1.0 + cosio } else { 1.5e-12 };
+    -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio) / denominator
+}
+
+// Days since 1949 December 31 00:00 UT, which the deep-space equations
+// count from. This goes through a Julian date as Vallado's jday does;
+// highly eccentric deep-space orbits are sensitive enough to the lunar
+// and solar phases that the verification vectors depend on its rounding.
+fn afspc_epoch(epoch: DateTime<Utc>) -> f64 {
+    let (year, month) = (epoch.year() as i64, epoch.month() as i64);
+    let days = 367 * year - 7 * (year + (month + 9) / 12) / 4 + 275 * month / 9 + epoch.day() as i64;
+    let seconds = epoch.second() as f64 + epoch.nanosecond() as f64 * 1e-9;
+    let julian_date = days as f64 + 1721013.5 + ((seconds / 60.0 + epoch.minute() as f64) / 60.0 + epoch.hour() as f64) / 24.0;
+    julian_date - 2433281.5
+}
+
+// Minutes from the epoch to a time, to the nanosecond.
+pub(crate) fn minutes_since(epoch: DateTime<Utc>, time: DateTime<Utc>) -> f64 {
+    let difference = time - epoch;
+    let seconds = difference.num_seconds();
+    let nanoseconds = (difference - chrono::Duration::seconds(seconds)).num_nanoseconds().unwrap_or(0);
+    (seconds as f64 + nanoseconds as f64 * 1e-9) / 60.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_tle, parse_tle_with_checksum, ChecksumMode};
+
+    const VERIFICATION: &str = include_str!("sgp4/verification.txt");
+
+    const ISS: &str = "ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791";
+
+    // Runs each case of the verification set, checking positions to a
+    // millimetre and velocities to a micrometre per second.
+    #[test]
+    fn test_verification_vectors() {
+        for case in VERIFICATION.split("\n\n").skip(1) {
+            let lines: Vec<&str> = case.lines().filter(|line| !line.starts_with('#')).collect();
+            let tle = parse_tle_with_checksum(&lines[..2].join("\n"), ChecksumMode::Off).unwrap();
+            let sgp4 = Sgp4::new(&tle);
+
+            for line in &lines[2..] {
+                let values: Vec<&str> = line.split_whitespace().collect();
+                let tsince: f64 = values[0].parse().unwrap();
+                let result = sgp4.as_ref().map_err(|e| *e).and_then(|sgp4| sgp4.propagate(tsince));
+                if values[1] == "error" {
+                    let code: u8 = values[2].parse().unwrap();
+                    assert_eq!(result.map_err(|e| e.code()), Err(code), "{} at {}", tle.satellite_number, tsince);
+                    continue;
+                }
+
+                let state = result.unwrap_or_else(|e| panic!("{} at {}: {}", tle.satellite_number, tsince, e));
+                for i in 0..3 {
+                    let position: f64 = values[1 + i].parse().unwrap();
+                    let velocity: f64 = values[4 + i].parse().unwrap();
+                    assert!((state.position[i] - position).abs() < 1e-6, "{} at {}: {:?}", tle.satellite_number, tsince, state);
+                    assert!((state.velocity[i] - velocity).abs() < 1e-9, "{} at {}: {:?}", tle.satellite_number, tsince, state);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_propagate_to() {
+        let tle = parse_tle(ISS).unwrap();
+        let sgp4 = Sgp4::new(&tle).unwrap();
+        assert_eq!(sgp4.propagate_to(tle.epoch), sgp4.propagate(0.0));
+        let later = tle.epoch + chrono::Duration::milliseconds(90_500);
+        assert_eq!(sgp4.propagate_to(later), sgp4.propagate(90.5 / 60.0));
+
+        let state = sgp4.propagate(0.0).unwrap();
+        let radius = state.position.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let speed = state.velocity.iter().map(|x| x * x).sum::<f64>().sqrt();
+        assert!((6780.0..6800.0).contains(&radius), "{}", radius);
+        assert!((7.6..7.7).contains(&speed), "{}", speed);
+    }
+
+    // A Molniya orbit in the 12 hour resonance stays between its perigee
+    // and apogee radii.
+    #[test]
+    fn test_deep_space() {
+        let arktika = parse_tle("ARKTIKA-M 1
+1 47719U 21016A   24169.25177733  .00000188  00000+0  00000+0 0  9990
+2 47719  63.1394 151.7679 6941537 268.6991  17.9346  2.00610662 24142").unwrap();
+        assert!(arktika.period() >= DEEP_SPACE_PERIOD);
+        let sgp4 = Sgp4::new(&arktika).unwrap();
+        assert!(sgp4.is_deep_space());
+
+        let a = arktika.semi_major_axis(EarthModel::Wgs72);
+        let (perigee, apogee) = (a * (1.0 - arktika.eccentricity), a * (1.0 + arktika.eccentricity));
+        for minutes in (-1440..=2880).step_by(10) {
+            let state = sgp4.propagate(minutes as f64).unwrap();
+            let radius = state.position.iter().map(|x| x * x).sum::<f64>().sqrt();
+            assert!(radius > perigee - 50.0 && radius < apogee + 50.0, "{} at {}", radius, minutes);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut tle = parse_tle(ISS).unwrap();
+        tle.eccentricity = 1.0;
+        assert_eq!(Sgp4::new(&tle), Err(Sgp4Error::MeanEccentricityOutOfRange(1.0)));
+        tle.eccentricity = 0.0004885;
+        tle.mean_motion = 0.0;
+        assert_eq!(Sgp4::new(&tle).map_err(|e| e.code()), Err(2));
+
+        // reenters about 440 minutes after its epoch
+        let decaying = parse_tle_with_checksum("1 29141U 85108AA  06170.26783845  .99999999  00000-0  13519-0 0   718
+2 29141  82.4288 273.4882 0015848 277.2124  83.9133 15.93343074  6828", ChecksumMode::Off).unwrap();
+        let sgp4 = Sgp4::new(&decaying).unwrap();
+        assert!(sgp4.propagate(420.0).is_ok());
+        let decayed = sgp4.propagate(440.0);
+        assert!(matches!(decayed, Err(Sgp4Error::Decayed(radius)) if radius < 1.0));
+        assert_eq!(decayed.map_err(|e| e.code()), Err(6));
+
+        let molniya = parse_tle("1 25485U 98054A   24169.50833113  .00000117  00000+0  00000+0 0  9998
+2 25485  64.1300 214.7240 6804350 284.7004  14.8786  2.36478960189543").unwrap();
+        assert!(Sgp4::new(&molniya).unwrap().is_deep_space());
+        for tsince in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let result = Sgp4::new(&molniya).unwrap().propagate(tsince);
+            assert_eq!(result.map_err(|e| e.code()), Err(7));
+        }
+        assert!(!Sgp4::new(&parse_tle(ISS).unwrap()).unwrap().is_deep_space());
+    }
+}
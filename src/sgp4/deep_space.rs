@@ -0,0 +1,538 @@
+use core::f64::consts::{PI, TAU};
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::math::Float;
+
+
+// Solar and lunar mean motions (rad/min) and eccentricities.
+const ZNS: f64 = 1.19459e-5;
+const ZES: f64 = 0.01675;
+const ZNL: f64 = 1.5835218e-4;
+const ZEL: f64 = 0.05490;
+// The Earth's rotation rate in rad/min. Constants are written as Vallado
+// publishes them.
+#[allow(clippy::excessive_precision)]
+const RPTIM: f64 = 4.37526908801129966e-3;
+// The resonance integrator's step in minutes.
+const STEP: f64 = 720.0;
+
+// The elements the deep-space terms adjust, with Vallado's names.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Elements {
+    pub em: f64,
+    pub inclm: f64,
+    pub nodem: f64,
+    pub argpm: f64,
+    pub mm: f64,
+    pub nm: f64,
+}
+
+// Which geopotential resonance the orbit is close to, if any.
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum Resonance {
+    None,
+    // Geosynchronous, a period of about a day.
+    Synchronous { del1: f64, del2: f64, del3: f64 },
+    // Half a day with an eccentricity of at least 0.5, as Molniya orbits.
+    HalfDay { d: [f64; 10] },
+}
+
+// The lunar-solar perturbations and resonance terms of SDP4, from
+// Vallado's dscom, dsinit, dspace and dpper.
+#[derive(PartialEq, Debug, Clone)]
+pub(super) struct DeepSpace {
+    // Greenwich sidereal time at the epoch, in AFSPC's formulation.
+    gsto: f64,
+    argpo: f64,
+    argpdot: f64,
+    no: f64,
+
+    // lunar-solar periodic coefficients
+    e3: f64,
+    ee2: f64,
+    se2: f64,
+    se3: f64,
+    sgh2: f64,
+    sgh3: f64,
+    sgh4: f64,
+    sh2: f64,
+    sh3: f64,
+    si2: f64,
+    si3: f64,
+    sl2: f64,
+    sl3: f64,
+    sl4: f64,
+    xgh2: f64,
+    xgh3: f64,
+    xgh4: f64,
+    xh2: f64,
+    xh3: f64,
+    xi2: f64,
+    xi3: f64,
+    xl2: f64,
+    xl3: f64,
+    xl4: f64,
+    zmol: f64,
+    zmos: f64,
+
+    // lunar-solar secular rates
+    dedt: f64,
+    didt: f64,
+    dmdt: f64,
+    dnodt: f64,
+    domdt: f64,
+
+    resonance: Resonance,
+    xfact: f64,
+    xlamo: f64,
+}
+
+// The coefficients dscom computes for the Sun and again for the Moon.
+struct ThirdBody {
+    s1: f64,
+    s2: f64,
+    s3: f64,
+    s4: f64,
+    s5: f64,
+    s6: f64,
+    s7: f64,
+    z1: f64,
+    z2: f64,
+    z3: f64,
+    z11: f64,
+    z12: f64,
+    z13: f64,
+    z21: f64,
+    z22: f64,
+    z23: f64,
+    z31: f64,
+    z32: f64,
+    z33: f64,
+}
+
+// The values of the near-Earth initialisation dsinit also needs.
+pub(super) struct Initial {
+    // Days since 1949 December 31 00:00 UT.
+    pub epoch: f64,
+    pub xke: f64,
+    pub ecco: f64,
+    pub argpo: f64,
+    pub inclo: f64,
+    pub nodeo: f64,
+    pub mo: f64,
+    pub no: f64,
+    pub mdot: f64,
+    pub argpdot: f64,
+    pub nodedot: f64,
+}
+
+impl DeepSpace {
+
+    pub(super) fn new(initial: &Initial) -> Self {
+        let Initial { epoch, xke, ecco: em, argpo, inclo, nodeo, mo, no, mdot, argpdot, nodedot } = *initial;
+        let gsto = afspc_sidereal_time(epoch);
+
+        // dscom
+        let (snodm, cnodm) = (nodeo.sin(), nodeo.cos());
+        let (sinomm, cosomm) = (argpo.sin(), argpo.cos());
+        let (sinim, cosim) = (inclo.sin(), inclo.cos());
+        let emsq = em * em;
+        let betasq = 1.0 - emsq;
+        let rtemsq = betasq.sqrt();
+
+        // the Moon's orbit at the epoch
+        let day = epoch + 18261.5;
+        let xnodce = (4.5236020 - 9.2422029e-4 * day) % TAU;
+        let (stem, ctem) = (xnodce.sin(), xnodce.cos());
+        let zcosil = 0.91375164 - 0.03568096 * ctem;
+        let zsinil = (1.0 - zcosil * zcosil).sqrt();
+        let zsinhl = 0.089683511 * stem / zsinil;
+        let zcoshl = (1.0 - zsinhl * zsinhl).sqrt();
+        let gam = 5.8351514 + 0.0019443680 * day;
+        let zx = 0.39785416 * stem / zsinil;
+        let zy = zcoshl * ctem + 0.91744867 * zsinhl * stem;
+        let zx = gam + zx.atan2(zy) - xnodce;
+        let (zsingl, zcosgl) = (zx.sin(), zx.cos());
+
+        let xnoi = 1.0 / no;
+        let third_body = |zcosg: f64, zsing: f64, zcosi: f64, zsini: f64, zcosh: f64, zsinh: f64, cc: f64| {
+            let a1 = zcosg * zcosh + zsing * zcosi * zsinh;
+            let a3 = -zsing * zcosh + zcosg * zcosi * zsinh;
+            let a7 = -zcosg * zsinh + zsing * zcosi * zcosh;
+            let a8 = zsing * zsini;
+            let a9 = zsing * zsinh + zcosg * zcosi * zcosh;
+            let a10 = zcosg * zsini;
+            let a2 = cosim * a7 + sinim * a8;
+            let a4 = cosim * a9 + sinim * a10;
+            let a5 = -sinim * a7 + cosim * a8;
+            let a6 = -sinim * a9 + cosim * a10;
+
+            let x1 = a1 * cosomm + a2 * sinomm;
+            let x2 = a3 * cosomm + a4 * sinomm;
+            let x3 = -a1 * sinomm + a2 * cosomm;
+            let x4 = -a3 * sinomm + a4 * cosomm;
+            let x5 = a5 * sinomm;
+            let x6 = a6 * sinomm;
+            let x7 = a5 * cosomm;
+            let x8 = a6 * cosomm;
+
+            let z31 = 12.0 * x1 * x1 - 3.0 * x3 * x3;
+            let z32 = 24.0 * x1 * x2 - 6.0 * x3 * x4;
+            let z33 = 12.0 * x2 * x2 - 3.0 * x4 * x4;
+            let z1 = 3.0 * (a1 * a1 + a2 * a2) + z31 * emsq;
+            let z2 = 6.0 * (a1 * a3 + a2 * a4) + z32 * emsq;
+            let z3 = 3.0 * (a3 * a3 + a4 * a4) + z33 * emsq;
+            let z11 = -6.0 * a1 * a5 + emsq * (-24.0 * x1 * x7 - 6.0 * x3 * x5);
+            let z12 = -6.0 * (a1 * a6 + a3 * a5) + emsq * (-24.0 * (x2 * x7 + x1 * x8) - 6.0 * (x3 * x6 + x4 * x5));
+            let z13 = -6.0 * a3 * a6 + emsq * (-24.0 * x2 * x8 - 6.0 * x4 * x6);
+            let z21 = 6.0 * a2 * a5 + emsq * (24.0 * x1 * x5 - 6.0 * x3 * x7);
+            let z22 = 6.0 * (a4 * a5 + a2 * a6) + emsq * (24.0 * (x2 * x5 + x1 * x6) - 6.0 * (x4 * x7 + x3 * x8));
+            let z23 = 6.0 * a4 * a6 + emsq * (24.0 * x2 * x6 - 6.0 * x4 * x8);
+            let s3 = cc * xnoi;
+            let s4 = s3 * rtemsq;
+            ThirdBody {
+                s1: -15.0 * em * s4,
+                s2: -0.5 * s3 / rtemsq,
+                s3,
+                s4,
+                s5: x1 * x3 + x2 * x4,
+                s6: x2 * x3 + x1 * x4,
+                s7: x2 * x4 - x1 * x3,
+                z1: z1 + z1 + betasq * z31,
+                z2: z2 + z2 + betasq * z32,
+                z3: z3 + z3 + betasq * z33,
+                z11,
+                z12,
+                z13,
+                z21,
+                z22,
+                z23,
+                z31,
+                z32,
+                z33,
+            }
+        };
+        let sun = third_body(0.1945905, -0.98088458, 0.91744867, 0.39785416, cnodm, snodm, 2.9864797e-6);
+        let moon = third_body(
+            zcosgl,
+            zsingl,
+            zcosil,
+            zsinil,
+            zcoshl * cnodm + zsinhl * snodm,
+            snodm * zcoshl - cnodm * zsinhl,
+            4.7968065e-7,
+        );
+        let zmol = (4.7199672 + 0.22997150 * day - gam) % TAU;
+        let zmos = (6.2565837 + 0.017201977 * day) % TAU;
+
+        // dsinit: the lunar-solar secular rates
+        let near_equatorial = !(5.2359877e-2..=PI - 5.2359877e-2).contains(&inclo);
+        let ses = sun.s1 * ZNS * sun.s5;
+        let sis = sun.s2 * ZNS * (sun.z11 + sun.z13);
+        let sls = -ZNS * sun.s3 * (sun.z1 + sun.z3 - 14.0 - 6.0 * emsq);
+        let sghs = sun.s4 * ZNS * (sun.z31 + sun.z33 - 6.0);
+        let mut shs = if near_equatorial { 0.0 } else { -ZNS * sun.s2 * (sun.z21 + sun.z23) };
+        if sinim != 0.0 {
+            shs /= sinim;
+        }
+        let sgs = sghs - cosim * shs;
+
+        let dedt = ses + moon.s1 * ZNL * moon.s5;
+        let didt = sis + moon.s2 * ZNL * (moon.z11 + moon.z13);
+        let dmdt = sls - ZNL * moon.s3 * (moon.z1 + moon.z3 - 14.0 - 6.0 * emsq);
+        let sghl = moon.s4 * ZNL * (moon.z31 + moon.z33 - 6.0);
+        let shll = if near_equatorial { 0.0 } else { -ZNL * moon.s2 * (moon.z21 + moon.z23) };
+        let mut domdt = sgs + sghl;
+        let mut dnodt = shs;
+        if sinim != 0.0 {
+            domdt -= cosim / sinim * shll;
+            dnodt += shll / sinim;
+        }
+
+        // dsinit: the resonance terms
+        let theta = gsto % TAU;
+        let aonv = (no / xke).powf(2.0 / 3.0);
+        let (mut resonance, mut xfact, mut xlamo) = (Resonance::None, 0.0, 0.0);
+        if no < 0.0052359877 && no > 0.0034906585 {
+            let g200 = 1.0 + emsq * (-2.5 + 0.8125 * emsq);
+            let g310 = 1.0 + 2.0 * emsq;
+            let g300 = 1.0 + emsq * (-6.0 + 6.60937 * emsq);
+            let f220 = 0.75 * (1.0 + cosim) * (1.0 + cosim);
+            let f311 = 0.9375 * sinim * sinim * (1.0 + 3.0 * cosim) - 0.75 * (1.0 + cosim);
+            let f330 = 1.875 * (1.0 + cosim) * (1.0 + cosim) * (1.0 + cosim);
+            let del1 = 3.0 * no * no * aonv * aonv;
+            resonance = Resonance::Synchronous {
+                del1: del1 * f311 * g310 * 2.1460748e-6 * aonv,
+                del2: 2.0 * del1 * f220 * g200 * 1.7891679e-6,
+                del3: 3.0 * del1 * f330 * g300 * 2.2123015e-7 * aonv,
+            };
+            xlamo = (mo + nodeo + argpo - theta) % TAU;
+            xfact = mdot + argpdot + nodedot - RPTIM + dmdt + domdt + dnodt - no;
+        } else if (8.26e-3..=9.24e-3).contains(&no) && em >= 0.5 {
+            let eoc = em * emsq;
+            let g201 = -0.306 - (em - 0.64) * 0.440;
+            let (g211, g310, g322, g410, g422, g520);
+            if em <= 0.65 {
+                g211 = 3.616 - 13.2470 * em + 16.2900 * emsq;
+                g310 = -19.302 + 117.3900 * em - 228.4190 * emsq + 156.5910 * eoc;
+                g322 = -18.9068 + 109.7927 * em - 214.6334 * emsq + 146.5816 * eoc;
+                g410 = -41.122 + 242.6940 * em - 471.0940 * emsq + 313.9530 * eoc;
+                g422 = -146.407 + 841.8800 * em - 1629.014 * emsq + 1083.4350 * eoc;
+                g520 = -532.114 + 3017.977 * em - 5740.032 * emsq + 3708.2760 * eoc;
+            } else {
+                g211 = -72.099 + 331.819 * em - 508.738 * emsq + 266.724 * eoc;
+                g310 = -346.844 + 1582.851 * em - 2415.925 * emsq + 1246.113 * eoc;
+                g322 = -342.585 + 1554.908 * em - 2366.899 * emsq + 1215.972 * eoc;
+                g410 = -1052.797 + 4758.686 * em - 7193.992 * emsq + 3651.957 * eoc;
+                g422 = -3581.690 + 16178.110 * em - 24462.770 * emsq + 12422.520 * eoc;
+                g520 = if em > 0.715 {
+                    -5149.66 + 29936.92 * em - 54087.36 * emsq + 31324.56 * eoc
+                } else {
+                    1464.74 - 4664.75 * em + 3763.64 * emsq
+                };
+            }
+            let (g533, g521, g532);
+            if em < 0.7 {
+                g533 = -919.22770 + 4988.6100 * em - 9064.7700 * emsq + 5542.21 * eoc;
+                g521 = -822.71072 + 4568.6173 * em - 8491.4146 * emsq + 5337.524 * eoc;
+                g532 = -853.66600 + 4690.2500 * em - 8624.7700 * emsq + 5341.4 * eoc;
+            } else {
+                g533 = -37995.780 + 161616.52 * em - 229838.20 * emsq + 109377.94 * eoc;
+                g521 = -51752.104 + 218913.95 * em - 309468.16 * emsq + 146349.42 * eoc;
+                g532 = -40023.880 + 170470.89 * em - 242699.48 * emsq + 115605.82 * eoc;
+            }
+
+            let cosisq = cosim * cosim;
+            let sini2 = sinim * sinim;
+            let f220 = 0.75 * (1.0 + 2.0 * cosim + cosisq);
+            let f221 = 1.5 * sini2;
+            let f321 = 1.875 * sinim * (1.0 - 2.0 * cosim - 3.0 * cosisq);
+            let f322 = -1.875 * sinim * (1.0 + 2.0 * cosim - 3.0 * cosisq);
+            let f441 = 35.0 * sini2 * f220;
+            let f442 = 39.3750 * sini2 * sini2;
+            let f522 = 9.84375 * sinim * (sini2 * (1.0 - 2.0 * cosim - 5.0 * cosisq)
+                + 0.33333333 * (-2.0 + 4.0 * cosim + 6.0 * cosisq));
+            let f523 = sinim * (4.92187512 * sini2 * (-2.0 - 4.0 * cosim + 10.0 * cosisq)
+                + 6.56250012 * (1.0 + 2.0 * cosim - 3.0 * cosisq));
+            let f542 = 29.53125 * sinim * (2.0 - 8.0 * cosim + cosisq * (-12.0 + 8.0 * cosim + 10.0 * cosisq));
+            let f543 = 29.53125 * sinim * (-2.0 - 8.0 * cosim + cosisq * (12.0 + 8.0 * cosim - 10.0 * cosisq));
+
+            let mut temp1 = 3.0 * (no * no) * (aonv * aonv);
+            let temp = temp1 * 1.7891679e-6;
+            let (d2201, d2211) = (temp * f220 * g201, temp * f221 * g211);
+            temp1 *= aonv;
+            let temp = temp1 * 3.7393792e-7;
+            let (d3210, d3222) = (temp * f321 * g310, temp * f322 * g322);
+            temp1 *= aonv;
+            let temp = 2.0 * temp1 * 7.3636953e-9;
+            let (d4410, d4422) = (temp * f441 * g410, temp * f442 * g422);
+            temp1 *= aonv;
+            let temp = temp1 * 1.1428639e-7;
+            let (d5220, d5232) = (temp * f522 * g520, temp * f523 * g532);
+            let temp = 2.0 * temp1 * 2.1765803e-9;
+            let (d5421, d5433) = (temp * f542 * g521, temp * f543 * g533);
+            resonance = Resonance::HalfDay { d: [d2201, d2211, d3210, d3222, d4410, d4422, d5220, d5232, d5421, d5433] };
+            xlamo = (mo + nodeo + nodeo - theta - theta) % TAU;
+            xfact = mdot + dmdt + 2.0 * (nodedot + dnodt - RPTIM) - no;
+        }
+
+        DeepSpace {
+            gsto,
+            argpo,
+            argpdot,
+            no,
+            e3: 2.0 * moon.s1 * moon.s7,
+            ee2: 2.0 * moon.s1 * moon.s6,
+            se2: 2.0 * sun.s1 * sun.s6,
+            se3: 2.0 * sun.s1 * sun.s7,
+            sgh2: 2.0 * sun.s4 * sun.z32,
+            sgh3: 2.0 * sun.s4 * (sun.z33 - sun.z31),
+            sgh4: -18.0 * sun.s4 * ZES,
+            sh2: -2.0 * sun.s2 * sun.z22,
+            sh3: -2.0 * sun.s2 * (sun.z23 - sun.z21),
+            si2: 2.0 * sun.s2 * sun.z12,
+            si3: 2.0 * sun.s2 * (sun.z13 - sun.z11),
+            sl2: -2.0 * sun.s3 * sun.z2,
+            sl3: -2.0 * sun.s3 * (sun.z3 - sun.z1),
+            sl4: -2.0 * sun.s3 * (-21.0 - 9.0 * emsq) * ZES,
+            xgh2: 2.0 * moon.s4 * moon.z32,
+            xgh3: 2.0 * moon.s4 * (moon.z33 - moon.z31),
+            xgh4: -18.0 * moon.s4 * ZEL,
+            xh2: -2.0 * moon.s2 * moon.z22,
+            xh3: -2.0 * moon.s2 * (moon.z23 - moon.z21),
+            xi2: 2.0 * moon.s2 * moon.z12,
+            xi3: 2.0 * moon.s2 * (moon.z13 - moon.z11),
+            xl2: -2.0 * moon.s3 * moon.z2,
+            xl3: -2.0 * moon.s3 * (moon.z3 - moon.z1),
+            xl4: -2.0 * moon.s3 * (-21.0 - 9.0 * emsq) * ZEL,
+            zmol,
+            zmos,
+            dedt,
+            didt,
+            dmdt,
+            dnodt,
+            domdt,
+            resonance,
+            xfact,
+            xlamo,
+        }
+    }
+
+    // dspace: adds the lunar-solar secular rates and integrates the
+    // resonance terms from the epoch to t minutes.
+    pub(super) fn secular(&self, t: f64, elements: &mut Elements) {
+        let theta = (self.gsto + t * RPTIM) % TAU;
+        elements.em += self.dedt * t;
+        elements.inclm += self.didt * t;
+        elements.argpm += self.domdt * t;
+        elements.nodem += self.dnodt * t;
+        elements.mm += self.dmdt * t;
+        if self.resonance == Resonance::None {
+            return;
+        }
+
+        // Euler-Maclaurin integration in 720 minute steps. Vallado keeps
+        // the integrator's state between calls; starting from the epoch
+        // each time takes the same steps, so gives the same result.
+        let delt = if t > 0.0 { STEP } else { -STEP };
+        let (mut atime, mut xni, mut xli) = (0.0, self.no, self.xlamo);
+        let (xndt, xldot, xnddt, ft) = loop {
+            let (xndt, xnddt) = self.resonance_rates(xli, atime);
+            let xldot = xni + self.xfact;
+            let xnddt = xnddt * xldot;
+            if (t - atime).abs() < STEP {
+                break (xndt, xldot, xnddt, t - atime);
+            }
+            xli += xldot * delt + xndt * STEP * STEP / 2.0;
+            xni += xndt * delt + xnddt * STEP * STEP / 2.0;
+            atime += delt;
+        };
+
+        elements.nm = xni + xndt * ft + xnddt * ft * ft * 0.5;
+        let xl = xli + xldot * ft + xndt * ft * ft * 0.5;
+        elements.mm = match self.resonance {
+            Resonance::Synchronous { .. } => xl - elements.nodem - elements.argpm + theta,
+            _ => xl - 2.0 * elements.nodem + 2.0 * theta,
+        };
+    }
+
+    // The rate of the mean motion and the factor of its derivative.
+    fn resonance_rates(&self, xli: f64, atime: f64) -> (f64, f64) {
+        const FASX2: f64 = 0.13130908;
+        const FASX4: f64 = 2.8843198;
+        const FASX6: f64 = 0.37448087;
+        const G22: f64 = 5.7686396;
+        const G32: f64 = 0.95240898;
+        const G44: f64 = 1.8014998;
+        const G52: f64 = 1.0508330;
+        const G54: f64 = 4.4108898;
+
+        match self.resonance {
+            Resonance::None => (0.0, 0.0),
+            Resonance::Synchronous { del1, del2, del3 } => (
+                del1 * (xli - FASX2).sin() + del2 * (2.0 * (xli - FASX4)).sin() + del3 * (3.0 * (xli - FASX6)).sin(),
+                del1 * (xli - FASX2).cos() + 2.0 * del2 * (2.0 * (xli - FASX4)).cos()
+                    + 3.0 * del3 * (3.0 * (xli - FASX6)).cos(),
+            ),
+            Resonance::HalfDay { d: [d2201, d2211, d3210, d3222, d4410, d4422, d5220, d5232, d5421, d5433] } => {
+                let xomi = self.argpo + self.argpdot * atime;
+                let x2omi = xomi + xomi;
+                let x2li = xli + xli;
+                let xndt = d2201 * (x2omi + xli - G22).sin() + d2211 * (xli - G22).sin()
+                    + d3210 * (xomi + xli - G32).sin() + d3222 * (-xomi + xli - G32).sin()
+                    + d4410 * (x2omi + x2li - G44).sin() + d4422 * (x2li - G44).sin()
+                    + d5220 * (xomi + xli - G52).sin() + d5232 * (-xomi + xli - G52).sin()
+                    + d5421 * (xomi + x2li - G54).sin() + d5433 * (-xomi + x2li - G54).sin();
+                let xnddt = d2201 * (x2omi + xli - G22).cos() + d2211 * (xli - G22).cos()
+                    + d3210 * (xomi + xli - G32).cos() + d3222 * (-xomi + xli - G32).cos()
+                    + d5220 * (xomi + xli - G52).cos() + d5232 * (-xomi + xli - G52).cos()
+                    + 2.0 * (d4410 * (x2omi + x2li - G44).cos() + d4422 * (x2li - G44).cos()
+                        + d5421 * (xomi + x2li - G54).cos() + d5433 * (-xomi + x2li - G54).cos());
+                (xndt, xnddt)
+            }
+        }
+    }
+
+    // dpper: adds the lunar-solar periodics t minutes from the epoch, with
+    // Lyddane's modification below 0.2 radians of inclination.
+    pub(super) fn periodics(&self, t: f64, elements: &mut Elements) {
+        let solar = self.zmos + ZNS * t;
+        let (f2, f3, sinzf) = periodic_factors(solar + 2.0 * ZES * solar.sin());
+        let ses = self.se2 * f2 + self.se3 * f3;
+        let sis = self.si2 * f2 + self.si3 * f3;
+        let sls = self.sl2 * f2 + self.sl3 * f3 + self.sl4 * sinzf;
+        let sghs = self.sgh2 * f2 + self.sgh3 * f3 + self.sgh4 * sinzf;
+        let shs = self.sh2 * f2 + self.sh3 * f3;
+
+        let lunar = self.zmol + ZNL * t;
+        let (f2, f3, sinzf) = periodic_factors(lunar + 2.0 * ZEL * lunar.sin());
+        let sel = self.ee2 * f2 + self.e3 * f3;
+        let sil = self.xi2 * f2 + self.xi3 * f3;
+        let sll = self.xl2 * f2 + self.xl3 * f3 + self.xl4 * sinzf;
+        let sghl = self.xgh2 * f2 + self.xgh3 * f3 + self.xgh4 * sinzf;
+        let shll = self.xh2 * f2 + self.xh3 * f3;
+
+        let pe = ses + sel;
+        let pinc = sis + sil;
+        let pl = sls + sll;
+        let mut pgh = sghs + sghl;
+        let mut ph = shs + shll;
+
+        elements.inclm += pinc;
+        elements.em += pe;
+        let (sinip, cosip) = (elements.inclm.sin(), elements.inclm.cos());
+        if elements.inclm >= 0.2 {
+            ph /= sinip;
+            pgh -= cosip * ph;
+            elements.argpm += pgh;
+            elements.nodem += ph;
+            elements.mm += pl;
+        } else {
+            let (sinop, cosop) = (elements.nodem.sin(), elements.nodem.cos());
+            let alfdp = sinip * sinop + (ph * cosop + pinc * cosip * sinop);
+            let betdp = sinip * cosop + (-ph * sinop + pinc * cosip * cosop);
+            let mut nodep = elements.nodem % TAU;
+            if nodep < 0.0 {
+                nodep += TAU;
+            }
+            let xls = (elements.mm + elements.argpm + cosip * nodep) + (pl + pgh - pinc * nodep * sinip);
+            let xnoh = nodep;
+            nodep = alfdp.atan2(betdp);
+            if nodep < 0.0 {
+                nodep += TAU;
+            }
+            if (xnoh - nodep).abs() > PI {
+                nodep += if nodep < xnoh { TAU } else { -TAU };
+            }
+            elements.nodem = nodep;
+            elements.mm += pl;
+            elements.argpm = xls - elements.mm - cosip * nodep;
+        }
+    }
+}
+
+fn periodic_factors(zf: f64) -> (f64, f64, f64) {
+    let sinzf = zf.sin();
+    (0.5 * sinzf * sinzf - 0.25, -0.5 * sinzf * zf.cos(), sinzf)
+}
+
+// Greenwich sidereal time in radians, from days since 1949 December 31
+// 00:00 UT, as AFSPC computes it. This differs from the IAU 1982 model in
+// the last few digits, which the verification vectors depend on.
+fn afspc_sidereal_time(epoch: f64) -> f64 {
+    #[allow(clippy::excessive_precision)]
+    const C1: f64 = 1.72027916940703639e-2;
+    const THGR70: f64 = 1.7321343856509374;
+    #[allow(clippy::excessive_precision)]
+    const FK5R: f64 = 5.07551419432269442e-15;
+
+    let ts70 = epoch - 7305.0;
+    let ds70 = (ts70 + 1.0e-8).floor();
+    let tfrac = ts70 - ds70;
+    let gsto = (THGR70 + C1 * ds70 + (C1 + TAU) * tfrac + ts70 * ts70 * FK5R) % TAU;
+    if gsto < 0.0 {
+        gsto + TAU
+    } else {
+        gsto
+    }
+}
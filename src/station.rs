@@ -0,0 +1,173 @@
+use crate::frame::{geodetic_to_ecef, Geodetic};
+use crate::parse::TLE;
+use crate::sgp4::{Sgp4, Sgp4Error};
+use alloc::vec::Vec;
+use chrono::{DateTime, Utc};
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::math::Float;
+
+
+// An antenna at a fixed point on the Earth. The horizon mask is a list of
+// (azimuth, minimum elevation) points in degrees, interpolated linearly
+// between neighbouring azimuths and wrapping at north. Without one the
+// minimum elevation is 0 everywhere.
+#[derive(PartialEq, Debug, Clone)]
+pub struct GroundStation {
+    position: Geodetic,
+    ecef: [f64; 3],
+    horizon_mask: Vec<(f64, f64)>,
+}
+
+// Where to point at a satellite from a station. Azimuth is in degrees
+// clockwise from north in [0, 360), elevation in degrees above the
+// horizon, range in km and range-rate in km/s, positive when receding.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct LookAngles {
+    pub time: DateTime<Utc>,
+    pub azimuth: f64,
+    pub elevation: f64,
+    pub range: f64,
+    pub range_rate: f64,
+}
+
+impl GroundStation {
+    pub fn new(position: Geodetic) -> Self {
+        GroundStation {position, ecef: geodetic_to_ecef(position), horizon_mask: Vec::new()}
+    }
+
+    pub fn with_horizon_mask(mut self, mut horizon_mask: Vec<(f64, f64)>) -> Self {
+        for point in horizon_mask.iter_mut() {
+            point.0 = point.0.rem_euclid(360.0);
+        }
+        horizon_mask.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.horizon_mask = horizon_mask;
+        self
+    }
+
+    pub fn position(&self) -> Geodetic {
+        self.position
+    }
+
+    // The lowest elevation the station can see at an azimuth.
+    pub fn minimum_elevation(&self, azimuth: f64) -> f64 {
+        let mask = &self.horizon_mask;
+        if mask.is_empty() {
+            return 0.0;
+        }
+        let azimuth = azimuth.rem_euclid(360.0);
+        let next = mask.iter().position(|point| point.0 > azimuth).unwrap_or(0);
+        let (after, before) = (mask[next], mask[(next + mask.len() - 1) % mask.len()]);
+        let span = (after.0 - before.0).rem_euclid(360.0);
+        if span == 0.0 {
+            return before.1;
+        }
+        before.1 + (after.1 - before.1) * (azimuth - before.0).rem_euclid(360.0) / span
+    }
+
+    // Whether the satellite is above the horizon mask.
+    pub fn is_visible(&self, look_angles: &LookAngles) -> bool {
+        look_angles.elevation >= self.minimum_elevation(look_angles.azimuth)
+    }
+
+    pub fn look_angles(&self, tle: &TLE, time: DateTime<Utc>) -> Result<LookAngles, Sgp4Error> {
+        self.observe(&Sgp4::new(tle)?, time)
+    }
+
+    // Look angles at each of the times, initialising SGP4 once.
+    pub fn look_angles_series<I>(&self, tle: &TLE, times: I) -> Result<Vec<LookAngles>, Sgp4Error>
+    where
+        I: IntoIterator<Item = DateTime<Utc>>,
+    {
+        let sgp4 = Sgp4::new(tle)?;
+        times.into_iter().map(|time| self.observe(&sgp4, time)).collect()
+    }
+
+    pub(crate) fn observe(&self, sgp4: &Sgp4, time: DateTime<Utc>) -> Result<LookAngles, Sgp4Error> {
+        let state = sgp4.propagate_ecef(time, None)?;
+        let range_vector: [f64; 3] = core::array::from_fn(|i| state.position[i] - self.ecef[i]);
+        let range = dot(range_vector, range_vector).sqrt();
+
+        // rotate into the local east, north, up frame
+        let (sin_latitude, cos_latitude) = (self.position.latitude.to_radians().sin(), self.position.latitude.to_radians().cos());
+        let (sin_longitude, cos_longitude) = (self.position.longitude.to_radians().sin(), self.position.longitude.to_radians().cos());
+        let [x, y, z] = range_vector;
+        let east = -sin_longitude * x + cos_longitude * y;
+        let north = -sin_latitude * cos_longitude * x - sin_latitude * sin_longitude * y + cos_latitude * z;
+        let up = cos_latitude * cos_longitude * x + cos_latitude * sin_longitude * y + sin_latitude * z;
+
+        Ok(LookAngles {
+            time,
+            azimuth: east.atan2(north).to_degrees().rem_euclid(360.0),
+            elevation: up.atan2((east * east + north * north).sqrt()).to_degrees(),
+            range,
+            range_rate: dot(range_vector, state.velocity) / range,
+        })
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_tle;
+    use chrono::Duration;
+
+    fn iss() -> TLE {
+        parse_tle("ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791").unwrap()
+    }
+
+    #[test]
+    fn test_look_angles() {
+        let iss = iss();
+        let sgp4 = Sgp4::new(&iss).unwrap();
+        let times: Vec<_> = (0..8640).map(|step| iss.epoch + Duration::seconds(10 * step)).collect();
+
+        // straight below the satellite it is overhead at its altitude
+        let below = sgp4.propagate_geodetic(iss.epoch, None).unwrap();
+        let station = GroundStation::new(Geodetic {altitude: 0.0, ..below});
+        let overhead = station.look_angles(&iss, iss.epoch).unwrap();
+        assert!(overhead.elevation > 89.999, "{:?}", overhead);
+        assert!((overhead.range - below.altitude).abs() < 1e-6, "{:?}", overhead);
+        assert!(overhead.range_rate.abs() < 0.1, "{:?}", overhead);
+
+        let series = station.look_angles_series(&iss, times.iter().copied()).unwrap();
+        assert_eq!(series.len(), times.len());
+        assert_eq!(series[0], overhead);
+        for pair in series.windows(2) {
+            let [before, after] = [pair[0], pair[1]];
+            assert!((0.0..360.0).contains(&after.azimuth));
+            assert!((-90.0..=90.0).contains(&after.elevation));
+            // the range-rate agrees with the change in range
+            let rate = (after.range - before.range) / 10.0;
+            assert!((rate - (before.range_rate + after.range_rate) / 2.0).abs() < 0.05, "{:?} {:?}", before, after);
+        }
+        assert!(series.iter().any(|look_angles| look_angles.elevation < -60.0));
+    }
+
+    #[test]
+    fn test_horizon_mask() {
+        let iss = iss();
+        let station = GroundStation::new(Geodetic {latitude: 52.0, longitude: 0.0, altitude: 0.1});
+        assert_eq!(station.minimum_elevation(123.0), 0.0);
+
+        let station = station.with_horizon_mask(vec![(0.0, 10.0), (90.0, 20.0), (-90.0, 5.0)]);
+        assert_eq!(station.minimum_elevation(0.0), 10.0);
+        assert_eq!(station.minimum_elevation(45.0), 15.0);
+        assert_eq!(station.minimum_elevation(180.0), 12.5);
+        assert_eq!(station.minimum_elevation(-45.0), 7.5);
+        assert_eq!(station.minimum_elevation(315.0), 7.5);
+
+        let look_angles = LookAngles {time: iss.epoch, azimuth: 45.0, elevation: 14.0, range: 2000.0, range_rate: 0.0};
+        assert!(!station.is_visible(&look_angles));
+        assert!(station.is_visible(&LookAngles {elevation: 15.0, ..look_angles}));
+
+        let flat = GroundStation::new(station.position()).with_horizon_mask(vec![(200.0, 3.0)]);
+        assert_eq!(flat.minimum_elevation(10.0), 3.0);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::parse::{parse_tle, ParseError, RecordSplitter, StrayLine, TLE};
+#[cfg(feature = "fetch")]
+use futures::stream::{self, Stream};
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+#[cfg(feature = "fetch")]
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+
+// Describes why a record could not be read from a stream of TLE's.
+#[derive(Debug)]
+pub enum ReadError {
+    // Reading from the underlying input failed.
+    Io(std::io::Error),
+    // A record was found but could not be parsed.
+    Parse(ParseError),
+    // A line that doesn't belong to any record.
+    StrayLine(StrayLine),
+}
+
+impl Display for ReadError {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> ::std::fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(formatter, "could not read TLE input: {}", e),
+            ReadError::Parse(e) => write!(formatter, "{}", e),
+            ReadError::StrayLine(line) => {
+                write!(formatter, "stray line {}: {:?}", line.line_number, line.text)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+impl From<std::io::Error> for ReadError {
+    fn from(e: std::io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+
+// Iterates over the TLE's in any BufRead, reading and parsing one record at a
+// time so large catalogs and archives never have to be held in memory.
+// Records are split with the same rules as split_tle_records and stray lines
+// are yielded as errors in the order they are found. Iteration ends after the
+// first read error.
+pub struct TleReader<R: BufRead> {
+    lines: std::io::Lines<R>,
+    splitter: RecordSplitter,
+    finished: bool,
+}
+
+impl<R: BufRead> TleReader<R> {
+    pub fn new(reader: R) -> Self {
+        TleReader { lines: reader.lines(), splitter: RecordSplitter::new(), finished: false }
+    }
+}
+
+impl<R: BufRead> Iterator for TleReader<R> {
+    type Item = Result<TLE, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.splitter.next_record() {
+                return Some(parse_record(item));
+            }
+            if self.finished {
+                return None;
+            }
+            match self.lines.next() {
+                Some(Ok(line)) => self.splitter.push_line(&line),
+                Some(Err(e)) => {
+                    // stop, as a failing reader may keep failing
+                    self.finished = true;
+                    return Some(Err(ReadError::Io(e)));
+                }
+                None => {
+                    self.splitter.finish();
+                    self.finished = true;
+                }
+            }
+        }
+    }
+}
+
+
+// Reads TLE's from any AsyncRead (a file, socket or HTTP body) as a Stream,
+// parsing each record as soon as its lines have arrived.
+#[cfg(feature = "fetch")]
+pub fn read_tles_async<R: AsyncRead + Unpin>(reader: R) -> impl Stream<Item = Result<TLE, ReadError>> {
+    let lines = BufReader::new(reader).lines();
+    stream::unfold(
+        (lines, RecordSplitter::new(), false),
+        |(mut lines, mut splitter, mut finished)| async move {
+            loop {
+                if let Some(item) = splitter.next_record() {
+                    return Some((parse_record(item), (lines, splitter, finished)));
+                }
+                if finished {
+                    return None;
+                }
+                match lines.next_line().await {
+                    Ok(Some(line)) => splitter.push_line(&line),
+                    Ok(None) => {
+                        splitter.finish();
+                        finished = true;
+                    }
+                    Err(e) => return Some((Err(ReadError::Io(e)), (lines, splitter, true))),
+                }
+            }
+        },
+    )
+}
+
+
+fn parse_record(item: Result<String, StrayLine>) -> Result<TLE, ReadError> {
+    match item {
+        Ok(record) => parse_tle(&record).map_err(ReadError::Parse),
+        Err(stray) => Err(ReadError::StrayLine(stray)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "fetch")]
+    use futures::StreamExt;
+    use std::io::Cursor;
+
+    const TLES: &str = "ISS (ZARYA)
+1 25544U 98067A   20045.18587073  .00000950  00000-0  25302-4 0  9990
+2 25544  51.6443 242.0161 0004885 264.6060 207.3845 15.49165514212791
+2 99999 ORPHANED LINE 2
+1 43890U 18111Q   20044.88470557  .00000320  00000-0  36258-4 0  9993
+2 43890  97.7009 312.6237 0003899   7.8254 352.3026 14.92889838 61757
+BROKEN
+1 25338U 98030A   24169.93801846  .00000329  00000+0  15393-3 0  9999
+2 25338  98.5680 197.0492 0009520 328.5342  31.5268 14.266054
+";
+
+    fn summarise(item: &Result<TLE, ReadError>) -> String {
+        match item {
+            Ok(tle) => tle.satellite_number.to_string(),
+            Err(ReadError::StrayLine(line)) => format!("stray {}", line.line_number),
+            Err(ReadError::Parse(e)) => format!("parse {}", e.field),
+            Err(ReadError::Io(_)) => "io".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_tle_reader() {
+        let items: Vec<String> = TleReader::new(Cursor::new(TLES)).map(|item| summarise(&item)).collect();
+        assert_eq!(items, vec!["25544", "stray 4", "43890", "parse mean_motion"]);
+    }
+
+    // Fails every read, like a socket returning EIO.
+    struct Failing;
+
+    impl std::io::Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("EIO"))
+        }
+    }
+
+    #[test]
+    fn test_tle_reader_stops_after_error() {
+        let first_record: String = TLES.lines().take(3).map(|line| format!("{}\n", line)).collect();
+        let reader = std::io::BufReader::new(std::io::Read::chain(Cursor::new(first_record), Failing));
+        let items: Vec<String> = TleReader::new(reader).take(10).map(|item| summarise(&item)).collect();
+        assert_eq!(items, vec!["25544", "io"]);
+    }
+
+    #[cfg(feature = "fetch")]
+    #[tokio::test]
+    async fn test_read_tles_async() {
+        let items: Vec<String> = read_tles_async(TLES.as_bytes())
+            .map(|item| summarise(&item))
+            .collect()
+            .await;
+        assert_eq!(items, vec!["25544", "stray 4", "43890", "parse mean_motion"]);
+    }
+}